@@ -0,0 +1,198 @@
+use crate::{
+    config::{BuildFile, Dependency, VangoFile, VersionReq},
+    error::Error,
+    fetch::{self, Located, Resolver, git::GitRef},
+    log_info_ln,
+};
+use std::str::FromStr;
+
+/// declares a dependency in the manifest once it is known to resolve to a compatible library, comments and ordering are kept
+pub fn add(build: &BuildFile, name: &str, dep: &Dependency, dev: bool) -> Result<(), Error> {
+    validate(build, name, dep)?;
+
+    let path = crate::manifest_path()?;
+    let mut doc: toml_edit::DocumentMut = std::fs::read_to_string(&path)?.parse()?;
+    let section = if dev { "dev-dependencies" } else { "dependencies" };
+    let table = doc
+        .entry(section)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .expect("the manifest was already parsed");
+    let replaced = table.insert(name, toml_edit::value(to_inline(dep))).is_some();
+
+    // never write a manifest that would fail to parse on the next build
+    let text = doc.to_string();
+    VangoFile::from_str(&text)?;
+    std::fs::write(&path, text)?;
+    if replaced {
+        log_info_ln!("updated '{name}' in '[{section}]' of '{}'", build.name);
+    } else {
+        log_info_ln!("added '{name}' to '[{section}]' of '{}'", build.name);
+    }
+    Ok(())
+}
+
+/// removes a dependency from the manifest, it is dropped from the lockfile by the next build
+pub fn remove(build: &BuildFile, name: &str, dev: bool) -> Result<(), Error> {
+    let path = crate::manifest_path()?;
+    let mut doc: toml_edit::DocumentMut = std::fs::read_to_string(&path)?.parse()?;
+    let section = if dev { "dev-dependencies" } else { "dependencies" };
+    doc.get_mut(section)
+        .and_then(|t| t.as_table_like_mut())
+        .and_then(|t| t.remove(name))
+        .ok_or(Error::DependencyNotDeclared(name.to_string(), section.to_string()))?;
+    std::fs::write(&path, doc.to_string())?;
+    log_info_ln!("removed '{name}' from '[{section}]' of '{}'", build.name);
+    Ok(())
+}
+
+/// checks that a new dependency exists, is a library, and does not require a newer standard than the project
+fn validate(build: &BuildFile, name: &str, dep: &Dependency) -> Result<(), Error> {
+    match dep {
+        Dependency::Git { rev, branch, tag, .. } => {
+            GitRef::from_fields(name, rev, branch, tag)?;
+        }
+        Dependency::Registry { version, .. } => {
+            VersionReq::from_str(version)?;
+        }
+        _ => (),
+    }
+
+    // lock changes are only ever persisted by builds
+    let mut resolver = Resolver::new(build, false, false)?;
+    let path = match fetch::locate(name, dep, &mut resolver)? {
+        Located::Root { path, .. } => path,
+        Located::Headers(headers) if !headers.is_dir() => return Err(Error::DirectoryNotFound(headers)),
+        Located::Headers(_) | Located::System(_) | Located::PkgConfig { .. } | Located::Vcpkg | Located::Conan(_) => return Ok(()),
+    };
+    if !path.is_dir() {
+        return Err(Error::DirectoryNotFound(path));
+    }
+
+    let save = std::env::current_dir()?;
+    std::env::set_current_dir(&path)?;
+    let manifest = crate::read_manifest().and_then(|m| VangoFile::from_str(&m));
+    std::env::set_current_dir(&save)?;
+    match manifest? {
+        VangoFile::Build(lib) if !lib.kind.is_lib() => Err(Error::InvalidDependency(name.to_string())),
+        VangoFile::Build(lib) if lib.interface > build.lang => {
+            Err(Error::IncompatibleCppStd(lib.name, lib.interface, build.name.clone(), build.lang))
+        }
+        VangoFile::Build(_) => Ok(()),
+        VangoFile::Lib(lib) => lib.validate(&build.name, build.lang).map(|_| ()),
+    }
+}
+
+fn to_inline(dep: &Dependency) -> toml_edit::InlineTable {
+    let mut table = toml_edit::InlineTable::new();
+    let features = match dep {
+        Dependency::Package {
+            src, features, version, ..
+        } => {
+            table.insert("path", src.to_string_lossy().as_ref().into());
+            if let Some(version) = version {
+                table.insert("version", version.into());
+            }
+            features
+        }
+        Dependency::Headers { headers, features } => {
+            table.insert("headers", headers.to_string_lossy().as_ref().into());
+            features
+        }
+        Dependency::Git {
+            git,
+            tag,
+            branch,
+            rev,
+            features,
+            ..
+        } => {
+            table.insert("git", git.into());
+            for (key, value) in [("tag", tag), ("branch", branch), ("rev", rev)] {
+                if let Some(value) = value {
+                    table.insert(key, value.into());
+                }
+            }
+            features
+        }
+        Dependency::Registry {
+            version,
+            registry,
+            features,
+        } => {
+            table.insert("version", version.into());
+            if let Some(registry) = registry {
+                table.insert("registry", registry.into());
+            }
+            features
+        }
+        Dependency::System { system } => {
+            table.insert("system", system.to_string_lossy().as_ref().into());
+            return table;
+        }
+        Dependency::Archive { .. } | Dependency::PkgConfig { .. } | Dependency::Conan { .. } => {
+            unreachable!("not supported by 'vango add'")
+        }
+    };
+    if !features.is_empty() {
+        table.insert("features", features.iter().collect::<toml_edit::Array>().into());
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    pub fn inline_dependencies() {
+        let git = Dependency::Git {
+            git: "https://github.com/fmtlib/fmt.git".to_string(),
+            tag: Some("10.2.1".to_string()),
+            branch: None,
+            rev: None,
+            path: None,
+            headers: None,
+            recipe: None,
+            features: vec!["os".to_string()],
+        };
+        let path = Dependency::Package {
+            src: PathBuf::from("../mylib"),
+            targets: Vec::new(),
+            recipe: None,
+            features: Vec::new(),
+            version: None,
+        };
+        let system = Dependency::System {
+            system: PathBuf::from("m"),
+        };
+
+        let mut doc: toml_edit::DocumentMut = "[dependencies]\n# keep me\nz = { system=\"z\" }\n".parse().unwrap();
+        for (name, dep) in [("fmt", &git), ("mylib", &path), ("m", &system)] {
+            doc["dependencies"]
+                .as_table_like_mut()
+                .unwrap()
+                .insert(name, toml_edit::value(to_inline(dep)));
+        }
+        let text = doc.to_string();
+        assert_eq!(
+            text,
+            "\
+[dependencies]
+# keep me
+z = { system=\"z\" }
+fmt = { git = \"https://github.com/fmtlib/fmt.git\", tag = \"10.2.1\", features = [\"os\"] }
+mylib = { path = \"../mylib\" }
+m = { system = \"m\" }
+"
+        );
+
+        // the generated tables deserialize back into the same variants
+        let table: toml::Table = toml::from_str(&text).unwrap();
+        let deps = &table["dependencies"];
+        assert_eq!(Dependency::from_value("fmt", deps["fmt"].clone()).unwrap(), git);
+        assert_eq!(Dependency::from_value("mylib", deps["mylib"].clone()).unwrap(), path);
+        assert_eq!(Dependency::from_value("m", deps["m"].clone()).unwrap(), system);
+    }
+}
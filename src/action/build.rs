@@ -1,20 +1,25 @@
+use super::export;
 use crate::{
     config::{BuildFile, BuildSettings, ProjKind, ToolChain, WarnLevel},
     error::Error,
     exec::{self, BuildInfo, prep},
-    fetch,
+    fetch::{self, Dependencies, Resolver},
     input::BuildSwitches,
+    log_warn_ln,
 };
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
-pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Result<(), Error> {
+/// builds a project, returns what its consumers must link in addition to its own binary (and the shared libraries
+/// it needs at runtime)
+pub fn build(build: &BuildFile, switches: &BuildSwitches, resolver: &mut Resolver, recursive: bool) -> Result<Dependencies, Error> {
     if !std::fs::exists("src").unwrap_or_default() {
         return Err(Error::MissingSource(build.name.clone()));
     }
 
     // extract settings for current profile
     let mut profile = build.get(&switches.profile)?.to_owned();
+    check_link_settings(&profile.settings, switches.toolchain)?;
 
     // BANDAID: collect all headers from all (direct) include directories (for incremental builds)
     let mut headers = Vec::new();
@@ -23,6 +28,8 @@ pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Re
             return Err(Error::MissingInclude(build.name.clone()));
         }
         profile.include.push("include".into());
+        export::write_header(build)?;
+        profile.include.push(export::HEADER_DIR.into());
     }
     for incdir in &profile.include {
         headers.extend(fetch::source_files(incdir, "h")?);
@@ -32,7 +39,7 @@ pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Re
     }
 
     // collect and flatten all dependency information into single SOA
-    let mut deps = fetch::libraries(build, &profile.baseprof, switches)?;
+    let mut deps = fetch::libraries(build, &profile.baseprof, switches, resolver)?;
     deps.defines.extend(profile.defines);
     if switches.is_test {
         deps.defines.push("VANGO_TEST".to_string());
@@ -44,6 +51,10 @@ pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Re
             deps.defines.push("VANGO_EXPORT_SHARED".to_string());
         }
     }
+    if let ProjKind::SharedLib { .. } = build.kind {
+        // '<NAME>_API' exports symbols while the library is built, and imports them everywhere else
+        deps.defines.push(format!("{}_EXPORTS", export::macro_prefix(&build.name)));
+    }
     deps.defines.push(format!("VANGO_PKG_NAME=\"{}\"", build.name));
     deps.defines.push(format!("VANGO_PKG_VERSION=\"{}\"", build.version));
     deps.defines.push(format!("VANGO_PKG_VERSION_MAJOR={}", build.version.major));
@@ -51,6 +62,84 @@ pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Re
     deps.defines.push(format!("VANGO_PKG_VERSION_PATCH={}", build.version.patch));
     deps.incdirs.extend(profile.include);
 
+    let (outdir, outfile, implib) = output_files(build, switches);
+
+    // replicate source directory hierarchy in output directory
+    prep::ensure_out_dirs(Path::new("src"), &outdir);
+
+    // public requirements of a library (its own and those of its dependencies) apply to all of its consumers
+    let mut interface = Dependencies::default();
+    if build.kind.is_lib() {
+        interface.incdirs = profile.public_include.iter().cloned().chain(deps.public_incdirs.iter().cloned()).collect();
+        interface.incdirs.push(export::HEADER_DIR.into());
+        interface.defines = profile.public_defines.iter().cloned().chain(deps.public_defines.iter().cloned()).collect();
+    }
+    // archives are not linked, so everything a static library links against is passed on to its consumers
+    if build.kind == ProjKind::StaticLib {
+        interface.libdirs = deps.libdirs.clone();
+        interface.rpaths = deps.rpaths.clone();
+        interface.archives = deps.archives.clone();
+        interface.relink = deps.relink.clone();
+        interface.linker_options = deps.linker_options.clone();
+        interface.requires = deps.requires.clone();
+    }
+    // shared libraries are loaded by every executable that ends up using the project, applications included
+    interface.shared_libs = deps.shared_libs.clone();
+    let requires = std::mem::take(&mut deps.requires);
+
+    let info = BuildInfo {
+        changed: settings_cache_changed(&deps, &profile.settings, switches, &outdir),
+        projkind: build.kind,
+        toolchain: switches.toolchain,
+        lang: build.lang,
+        cpprt: build.runtime.as_ref().map(|rt| rt.eq_ignore_ascii_case("c++")).unwrap_or_default(),
+        settings: profile.settings,
+        is_testexe: false,
+
+        defines: deps.defines,
+
+        srcdir: PathBuf::from("src"),
+        incdirs: deps.incdirs,
+        libdirs: deps.libdirs,
+        rpaths: deps.rpaths,
+        outdir: outdir.clone(),
+
+        pch: profile.pch,
+        sources: fetch::source_files(Path::new("src"), build.lang.src_ext()).unwrap(),
+        headers,
+        archives: deps.archives,
+        relink: deps.relink,
+        shared_libs: deps.shared_libs,
+        outfile: outfile.clone(),
+        implib: implib.clone(),
+        version: build.version,
+
+        // the project's own options come last, so they can override those of its dependencies
+        comp_args: deps.compiler_options.into_iter().chain(profile.compiler_options).collect(),
+        link_args: deps.linker_options.into_iter().chain(profile.linker_options).collect(),
+    };
+    exec::run_build(info, switches.echo, switches.verbose, recursive)?;
+    if build.kind == ProjKind::App && build.copy_shared {
+        copy_shared_libs(&interface.shared_libs, &outdir)?;
+    }
+
+    // consumers using other build systems find the library through pkg-config or CMake ('bin/debug' is a prefix)
+    if build.kind.is_lib() {
+        let layout = export::Layout {
+            prefix: std::env::current_dir()?,
+            libdir: outdir.clone(),
+            library: outfile,
+            implib: if cfg!(windows) { implib } else { None },
+            pkgconfig_dir: outdir.clone(),
+            cmake_dir: outdir,
+        };
+        export::write(build, &interface, &requires, &layout, &layout.prefix)?;
+    }
+    Ok(interface)
+}
+
+/// the output directory of a build and the files it produces, 'implib' is the import library of a DLL
+pub fn output_files(build: &BuildFile, switches: &BuildSwitches) -> (PathBuf, PathBuf, Option<PathBuf>) {
     // scope all output to correct directory
     let outdir = if switches.toolchain == ToolChain::system_default() {
         PathBuf::from("bin").join(switches.profile.to_string())
@@ -86,45 +175,65 @@ pub fn build(build: &BuildFile, switches: &BuildSwitches, recursive: bool) -> Re
             None,
         ),
     };
+    (outdir, outfile, implib)
+}
 
-    // replicate source directory hierarchy in output directory
-    prep::ensure_out_dirs(Path::new("src"), &outdir);
-
-    let info = BuildInfo {
-        changed: settings_cache_changed(deps.defines.clone(), &profile.settings, switches, &outdir),
-        projkind: build.kind,
-        toolchain: switches.toolchain,
-        lang: build.lang,
-        cpprt: build.runtime.as_ref().map(|rt| rt.eq_ignore_ascii_case("c++")).unwrap_or_default(),
-        settings: profile.settings,
-        is_testexe: false,
-
-        defines: deps.defines,
+/// linkers must be available to the toolchain, settings that are only supported by some are ignored elsewhere
+fn check_link_settings(settings: &BuildSettings, toolchain: ToolChain) -> Result<(), Error> {
+    if !toolchain.supports_linker(settings.linker) {
+        return Err(Error::UnsupportedLinker(settings.linker, toolchain));
+    }
+    if settings.icf && !toolchain.supports_icf(settings.linker) {
+        log_warn_ln!("identical code folding ('icf') needs lld, mold or gold with toolchain '{toolchain}' - ignored");
+    }
+    if settings.thin_archive && !toolchain.supports_thin_archive() {
+        log_warn_ln!("thin archives ('thin-archive') are not supported by the archiver of toolchain '{toolchain}' - ignored");
+    }
+    Ok(())
+}
 
-        srcdir: PathBuf::from("src"),
-        incdirs: deps.incdirs,
-        libdirs: deps.libdirs,
-        rpaths: deps.rpaths,
-        outdir,
+/// places the shared libraries of dependencies next to an executable ('copy-shared')
+fn copy_shared_libs(shared_libs: &[PathBuf], outdir: &Path) -> Result<(), Error> {
+    for lib in shared_libs {
+        place_shared_lib(lib, &outdir.join(lib.file_name().unwrap()))?;
+    }
+    Ok(())
+}
 
-        pch: profile.pch,
-        sources: fetch::source_files(Path::new("src"), build.lang.src_ext()).unwrap(),
-        headers,
-        archives: deps.archives,
-        relink: deps.relink,
-        outfile: outfile.clone(),
-        implib,
+/// links keep the versioned files of a library and its links apart, and never go stale
+#[cfg(unix)]
+fn place_shared_lib(lib: &Path, dest: &Path) -> Result<(), Error> {
+    let target = std::fs::canonicalize(lib)?;
+    if std::fs::read_link(dest).is_ok_and(|t| t == target) {
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(dest);
+    std::os::unix::fs::symlink(target, dest)?;
+    Ok(())
+}
 
-        comp_args: profile.compiler_options,
-        link_args: profile.linker_options,
-    };
-    exec::run_build(info, switches.echo, switches.verbose, recursive)
+/// DLLs are copied when they changed
+#[cfg(not(unix))]
+fn place_shared_lib(lib: &Path, dest: &Path) -> Result<(), Error> {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    if modified(dest).is_some_and(|d| modified(lib).is_some_and(|l| l <= d)) {
+        return Ok(());
+    }
+    std::fs::copy(lib, dest)?;
+    Ok(())
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(clippy::struct_excessive_bools)]
 struct BuildCache {
     defines: Vec<String>,
+    /// a dependency that moved (e.g. patched to a local copy) may be older than the previous build output
+    incdirs: Vec<PathBuf>,
+    libdirs: Vec<PathBuf>,
+    /// libraries discovered from installed packages do not appear in the manifest
+    archives: Vec<PathBuf>,
+    /// executables are linked with rpaths to the shared libraries of their dependencies
+    shared_libs: Vec<PathBuf>,
     opt_level: u32,
     opt_size: bool,
     opt_speed: bool,
@@ -132,11 +241,18 @@ struct BuildCache {
     iso_compliant: bool,
     warn_level: WarnLevel,
     warn_as_error: bool,
-    debug_info: bool,
+    debug_info: crate::config::DebugInfo,
+    strip: crate::config::Strip,
+    split_debuginfo: crate::config::SplitDebuginfo,
     runtime: crate::config::Runtime,
     aslr: bool,
     no_rtti: bool,
     no_except: bool,
+    visibility: crate::config::Visibility,
+    linker: crate::config::Linker,
+    thin_archive: bool,
+    dead_code_elimination: bool,
+    icf: bool,
     is_test: bool,
 
     pthreads: bool,
@@ -146,9 +262,13 @@ struct BuildCache {
     ubsan: bool,
 }
 
-fn settings_cache_changed(defines: Vec<String>, settings: &BuildSettings, switches: &BuildSwitches, outdir: &std::path::Path) -> bool {
+fn settings_cache_changed(deps: &fetch::Dependencies, settings: &BuildSettings, switches: &BuildSwitches, outdir: &std::path::Path) -> bool {
     let newcache = BuildCache {
-        defines,
+        defines: deps.defines.clone(),
+        incdirs: deps.incdirs.clone(),
+        libdirs: deps.libdirs.clone(),
+        archives: deps.archives.clone(),
+        shared_libs: deps.shared_libs.clone(),
         opt_level: settings.opt_level,
         opt_size: settings.opt_size,
         opt_speed: settings.opt_speed,
@@ -157,10 +277,17 @@ fn settings_cache_changed(defines: Vec<String>, settings: &BuildSettings, switch
         warn_level: settings.warn_level,
         warn_as_error: settings.warn_as_error,
         debug_info: settings.debug_info,
+        strip: settings.strip,
+        split_debuginfo: settings.split_debuginfo,
         runtime: settings.runtime,
         aslr: settings.aslr,
         no_rtti: settings.no_rtti,
         no_except: settings.no_except,
+        visibility: settings.visibility,
+        linker: settings.linker,
+        thin_archive: settings.thin_archive,
+        dead_code_elimination: settings.dead_code_elimination,
+        icf: settings.icf,
         is_test: switches.is_test,
 
         pthreads: settings.pthreads,
@@ -178,6 +305,10 @@ fn settings_cache_changed(defines: Vec<String>, settings: &BuildSettings, switch
 
         // settings that may or may not trigger project rebuilds
         newcache.defines != oldcache.defines
+            || newcache.incdirs != oldcache.incdirs
+            || newcache.libdirs != oldcache.libdirs
+            || newcache.archives != oldcache.archives
+            || newcache.shared_libs != oldcache.shared_libs
             || newcache.opt_level != oldcache.opt_level
             || newcache.opt_size != oldcache.opt_size
             || newcache.opt_speed != oldcache.opt_speed
@@ -186,10 +317,17 @@ fn settings_cache_changed(defines: Vec<String>, settings: &BuildSettings, switch
             || ((newcache.warn_level > oldcache.warn_level) && newcache.warn_as_error)
             || (newcache.warn_as_error && !oldcache.warn_as_error)
             || newcache.debug_info != oldcache.debug_info
+            || newcache.strip != oldcache.strip
+            || newcache.split_debuginfo != oldcache.split_debuginfo
             || newcache.runtime != oldcache.runtime
             || newcache.aslr != oldcache.aslr
             || newcache.no_rtti != oldcache.no_rtti
             || newcache.no_except != oldcache.no_except
+            || newcache.visibility != oldcache.visibility
+            || newcache.linker != oldcache.linker
+            || newcache.thin_archive != oldcache.thin_archive
+            || newcache.dead_code_elimination != oldcache.dead_code_elimination
+            || newcache.icf != oldcache.icf
             || newcache.is_test != oldcache.is_test
             || newcache.pthreads != oldcache.pthreads
             || newcache.asan != oldcache.asan
@@ -1,6 +1,7 @@
 use crate::{
-    config::{BuildFile, Dependency, LibFile, Profile, ProjKind, ToolChain, VangoFile, WarnLevel},
+    config::{BuildFile, LibFile, Profile, ProjKind, ToolChain, VangoFile, WarnLevel},
     error::Error,
+    fetch::{self, Resolver},
     log_info_ln,
 };
 use std::io::Write;
@@ -37,45 +38,51 @@ pub fn clangd(build: &BuildFile, block_output: bool) -> Result<(), Error> {
         writeln!(file, "-Wpedantic")?;
     }
 
-    let home = std::env::home_dir().unwrap();
+    // lock changes are only ever persisted by builds
+    let mut resolver = Resolver::new(build, false, false)?;
     let mut defines = Vec::new();
     let mut incdirs = Vec::new();
     let mut vcpkg = false;
 
-    for lib in &build.dependencies {
-        let path = match &lib.1 {
-            Dependency::Package { src, .. } => {
-                if src == "vcpkg" {
-                    vcpkg = true;
-                    continue;
-                } else {
-                    src.clone()
-                }
+    // tests are part of the project too, so their dev-dependencies are included
+    for lib in build.dependencies.iter().chain(&build.dev_dependencies) {
+        let patched = resolver.patch(&lib.0, &lib.1);
+        let dep = patched.as_ref().unwrap_or(&lib.1);
+        let path = match fetch::locate(&lib.0, dep, &mut resolver)? {
+            fetch::Located::Root { path, .. } => path,
+            fetch::Located::Headers(headers) => {
+                incdirs.push(headers);
+                continue;
             }
-            Dependency::Git { git, tag, .. } => {
-                let git = std::path::Path::new(&git);
-                let stem = git.file_stem().unwrap().to_string_lossy();
-                let path = home.join(format!(".vango/packages/{stem}"));
-                if !std::fs::exists(&path).unwrap() {
-                    crate::fetch::pull_git_repo(git, tag, &path);
-                }
-                path.clone()
+            fetch::Located::System(_) | fetch::Located::Conan(_) => continue,
+            fetch::Located::PkgConfig { module, version } => {
+                let module = fetch::pkgconfig::probe(&module, version.as_deref(), false, false)?;
+                incdirs.extend(module.incdirs);
+                defines.extend(module.defines);
+                continue;
             }
-            Dependency::Headers { headers, .. } => {
-                incdirs.push(headers.clone());
+            fetch::Located::Vcpkg => {
+                vcpkg = true;
                 continue;
             }
-            Dependency::System { .. } => continue,
         };
 
         if !std::fs::exists(&path).unwrap() {
             return Err(Error::DirectoryNotFound(path));
         }
+        if let Some(recipe) = dep.recipe() {
+            incdirs.push(fetch::recipe::include_dir(&path, recipe, ToolChain::system_default(), &Profile::Debug));
+            continue;
+        }
 
         let save = std::env::current_dir().unwrap();
         std::env::set_current_dir(&path).unwrap();
         let mut library = match VangoFile::from_str(&crate::read_manifest()?)? {
-            VangoFile::Build(build) => LibFile::from_build(build, ToolChain::system_default())?,
+            VangoFile::Build(build) => {
+                // the export header is generated by the first build of the library
+                incdirs.push(path.join(super::export::HEADER_DIR));
+                LibFile::from_build(build, ToolChain::system_default())?
+            }
             VangoFile::Lib(lib) => lib,
         };
         std::env::set_current_dir(&save).unwrap();
@@ -94,6 +101,9 @@ pub fn clangd(build: &BuildFile, block_output: bool) -> Result<(), Error> {
             writeln!(file, "-DVANGO_EXPORT_SHARED")?;
         }
     }
+    if let ProjKind::SharedLib { .. } = build.kind {
+        writeln!(file, "-D{}_EXPORTS", super::export::macro_prefix(&build.name))?;
+    }
     writeln!(file, "-DVANGO_DEBUG")?;
     writeln!(file, "-DVANGO_PKG_NAME=\"{}\"", build.name)?;
     writeln!(file, "-DVANGO_PKG_VERSION=\"{}\"", build.version)?;
@@ -110,7 +120,9 @@ pub fn clangd(build: &BuildFile, block_output: bool) -> Result<(), Error> {
         writeln!(file, "-I{}", inc.display())?;
     }
     if build.kind.is_lib() {
+        super::export::write_header(build)?;
         writeln!(file, "-Iinclude")?;
+        writeln!(file, "-I{}", super::export::HEADER_DIR)?;
     }
     writeln!(
         file,
@@ -0,0 +1,408 @@
+use crate::{
+    config::{BuildFile, ProjKind},
+    error::Error,
+    fetch::Dependencies,
+};
+use std::{
+    fmt::Write,
+    path::{Component, Path, PathBuf},
+};
+
+/// where the files of a built library package are, every path but 'prefix' (the project root of a build, or an
+/// install prefix) is relative to it
+pub struct Layout {
+    pub prefix: PathBuf,
+    pub libdir: PathBuf,
+    /// the file consumers link against, the import library of a DLL is 'implib'
+    pub library: PathBuf,
+    pub implib: Option<PathBuf>,
+    pub pkgconfig_dir: PathBuf,
+    pub cmake_dir: PathBuf,
+}
+
+/// writes the pkg-config and CMake package files of a library below 'dest' (the prefix, unless staged elsewhere), so that
+/// consumers using other build systems can find it, 'interface' holds the requirements the library passes on to its
+/// consumers and 'requires' the modules it links against, returns the files relative to 'dest'
+pub fn write(
+    build: &BuildFile,
+    interface: &Dependencies,
+    requires: &[String],
+    layout: &Layout,
+    dest: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    let files = [
+        (
+            layout.pkgconfig_dir.join(format!("{}.pc", build.name)),
+            pkg_config(build, interface, requires, layout),
+        ),
+        (
+            layout.cmake_dir.join(format!("{}Config.cmake", build.name)),
+            cmake_config(build, interface, layout),
+        ),
+        (
+            layout.cmake_dir.join(format!("{}ConfigVersion.cmake", build.name)),
+            cmake_version(build),
+        ),
+    ];
+    let mut written = Vec::new();
+    for (path, contents) in files {
+        let full = dest.join(&path);
+        // unchanged files keep their timestamps, consumers' build systems watch them
+        if std::fs::read_to_string(&full).ok().as_deref() != Some(contents.as_str()) {
+            std::fs::create_dir_all(full.parent().unwrap())?;
+            std::fs::write(&full, contents)?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn pkg_config(build: &BuildFile, interface: &Dependencies, requires: &[String], layout: &Layout) -> String {
+    let path = |p: &Path| {
+        let p = p.to_string_lossy().replace('\\', "/");
+        if Path::new(&p).is_absolute() {
+            pc_escape(&p)
+        } else {
+            format!("${{prefix}}/{}", pc_escape(&p))
+        }
+    };
+    let mut pc = String::new();
+    let _ = writeln!(pc, "prefix={}", pc_escape(&layout.prefix.to_string_lossy().replace('\\', "/")));
+    let _ = writeln!(pc, "includedir=${{prefix}}/include");
+    let _ = writeln!(pc, "libdir={}", path(&layout.libdir));
+    let _ = writeln!(pc);
+    let _ = writeln!(pc, "Name: {}", build.name);
+    let kind = if build.kind == ProjKind::StaticLib { "static" } else { "shared" };
+    let _ = writeln!(pc, "Description: {} {kind} library", build.name);
+    let _ = writeln!(pc, "Version: {}", build.version);
+    if !requires.is_empty() {
+        // only needed to link statically, their flags are still applied when compiling
+        let _ = writeln!(pc, "Requires.private: {}", requires.join(", "));
+    }
+
+    let mut cflags = vec!["-I${includedir}".to_string()];
+    cflags.extend(interface.incdirs.iter().map(|i| format!("-I{}", path(i))));
+    cflags.extend(interface.defines.iter().map(|d| format!("-D{}", pc_escape(d))));
+    let _ = writeln!(pc, "Cflags: {}", cflags.join(" "));
+    if build.kind.has_lib() {
+        let _ = writeln!(pc, "Libs: -L${{libdir}} -l{}", build.name);
+    }
+    let mut private: Vec<_> = interface.libdirs.iter().map(|l| format!("-L{}", path(l))).collect();
+    private.extend(interface.archives.iter().map(|a| format!("-l{}", pc_escape(&a.to_string_lossy()))));
+    private.extend(interface.linker_options.iter().map(|o| pc_escape(o)));
+    if !private.is_empty() {
+        let _ = writeln!(pc, "Libs.private: {}", private.join(" "));
+    }
+    pc
+}
+
+/// pkg-config splits fields like a shell does, and expands '${...}'
+fn pc_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            ' ' | '"' | '\'' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '$' => escaped.push_str("$$"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// an imported target '<name>::<name>', located relative to the config file so that installs can be moved
+fn cmake_config(build: &BuildFile, interface: &Dependencies, layout: &Layout) -> String {
+    let target = format!("{0}::{0}", build.name);
+    let prefix = format!("_{}_PREFIX", build.name.to_ascii_uppercase().replace(['-', '.'], "_"));
+    let path = |p: &Path| {
+        let p = p.to_string_lossy().replace('\\', "/");
+        if Path::new(&p).is_absolute() {
+            cmake_escape(&p)
+        } else {
+            format!("${{{prefix}}}/{}", cmake_escape(&p))
+        }
+    };
+    let list = |items: Vec<String>| items.join(";");
+    let up = layout.cmake_dir.components().filter(|c| matches!(c, Component::Normal(_))).count();
+
+    let mut cmake = String::new();
+    let _ = writeln!(cmake, "# generated by vango for {} {}", build.name, build.version);
+    let _ = writeln!(cmake, "if(TARGET {target})\n  return()\nendif()\n");
+    let _ = writeln!(
+        cmake,
+        "get_filename_component({prefix} \"${{CMAKE_CURRENT_LIST_DIR}}{}\" ABSOLUTE)\n",
+        "/..".repeat(up)
+    );
+    let kind = if build.kind == ProjKind::StaticLib { "STATIC" } else { "SHARED" };
+    let _ = writeln!(cmake, "add_library({target} {kind} IMPORTED)");
+    let _ = writeln!(cmake, "set_target_properties({target} PROPERTIES");
+    let _ = writeln!(cmake, "  IMPORTED_LOCATION \"{}\"", path(&layout.library));
+    if let Some(implib) = &layout.implib {
+        let _ = writeln!(cmake, "  IMPORTED_IMPLIB \"{}\"", path(implib));
+    }
+    if build.kind == ProjKind::StaticLib {
+        // a C++ archive must be linked by the C++ driver, even into C programs
+        let lang = if build.lang.is_cpp() { "CXX" } else { "C" };
+        let _ = writeln!(cmake, "  IMPORTED_LINK_INTERFACE_LANGUAGES \"{lang}\"");
+    }
+    let incdirs = std::iter::once(path(Path::new("include"))).chain(interface.incdirs.iter().map(|i| path(i)));
+    let _ = writeln!(cmake, "  INTERFACE_INCLUDE_DIRECTORIES \"{}\"", list(incdirs.collect()));
+    let properties = [
+        (
+            "INTERFACE_COMPILE_DEFINITIONS",
+            interface.defines.iter().map(|d| cmake_escape(d)).collect::<Vec<_>>(),
+        ),
+        ("INTERFACE_LINK_DIRECTORIES", interface.libdirs.iter().map(|l| path(l)).collect()),
+        (
+            "INTERFACE_LINK_LIBRARIES",
+            interface.archives.iter().map(|a| cmake_escape(&a.to_string_lossy())).collect(),
+        ),
+        (
+            "INTERFACE_LINK_OPTIONS",
+            interface.linker_options.iter().map(|o| cmake_escape(o)).collect(),
+        ),
+    ];
+    for (property, values) in properties {
+        if !values.is_empty() {
+            let _ = writeln!(cmake, "  {property} \"{}\"", list(values));
+        }
+    }
+    let _ = writeln!(cmake, ")\n");
+    let _ = writeln!(cmake, "unset({prefix})");
+    cmake
+}
+
+/// compatible versions follow semver like vango's own requirements: the same major version, or the same minor
+/// version before 1.0.0
+fn cmake_version(build: &BuildFile) -> String {
+    let v = build.version;
+    let (compatible, scope) = if v.major > 0 {
+        ("PACKAGE_FIND_VERSION_MAJOR", v.major.to_string())
+    } else {
+        (
+            "\"${PACKAGE_FIND_VERSION_MAJOR}.${PACKAGE_FIND_VERSION_MINOR}\"",
+            format!("0.{}", v.minor),
+        )
+    };
+    format!(
+        r#"# generated by vango for {name} {v}
+set(PACKAGE_VERSION "{v}")
+
+if(PACKAGE_FIND_VERSION_RANGE)
+  if(PACKAGE_VERSION VERSION_LESS PACKAGE_FIND_VERSION_MIN
+      OR (PACKAGE_FIND_VERSION_RANGE_MAX STREQUAL "INCLUDE" AND PACKAGE_VERSION VERSION_GREATER PACKAGE_FIND_VERSION_MAX)
+      OR (PACKAGE_FIND_VERSION_RANGE_MAX STREQUAL "EXCLUDE" AND PACKAGE_VERSION VERSION_GREATER_EQUAL PACKAGE_FIND_VERSION_MAX))
+    set(PACKAGE_VERSION_COMPATIBLE FALSE)
+  else()
+    set(PACKAGE_VERSION_COMPATIBLE TRUE)
+  endif()
+elseif(NOT PACKAGE_FIND_VERSION)
+  set(PACKAGE_VERSION_COMPATIBLE TRUE)
+elseif(PACKAGE_VERSION VERSION_LESS PACKAGE_FIND_VERSION OR NOT {compatible} STREQUAL "{scope}")
+  set(PACKAGE_VERSION_COMPATIBLE FALSE)
+else()
+  set(PACKAGE_VERSION_COMPATIBLE TRUE)
+  if(PACKAGE_FIND_VERSION STREQUAL PACKAGE_VERSION)
+    set(PACKAGE_VERSION_EXACT TRUE)
+  endif()
+endif()
+"#,
+        name = build.name
+    )
+}
+
+/// quoted CMake arguments expand '${...}' and end at an unescaped '"', ';' separates list items
+fn cmake_escape(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' | ';' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// generated headers of a library, part of its public include directories
+pub const HEADER_DIR: &str = "bin/include";
+
+/// the prefix of the macros of a library's export header, 'my-lib' is 'MY_LIB'
+pub fn macro_prefix(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// writes '<name>_export.h' to 'HEADER_DIR' (in the style of CMake's GenerateExportHeader), defining '<NAME>_API' to
+/// export a symbol from a shared library (and import it in its consumers), '<NAME>_NO_EXPORT' and the deprecation macros,
+/// the macros of a static library are empty
+pub fn write_header(build: &BuildFile) -> Result<PathBuf, Error> {
+    let path = Path::new(HEADER_DIR).join(format!("{}_export.h", build.name));
+    let contents = export_header(build);
+    if std::fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+        std::fs::create_dir_all(HEADER_DIR)?;
+        std::fs::write(&path, contents)?;
+    }
+    Ok(path)
+}
+
+fn export_header(build: &BuildFile) -> String {
+    let n = macro_prefix(&build.name);
+    let mut h = String::new();
+    let _ = writeln!(h, "/* generated by vango for '{}', do not edit */", build.name);
+    let _ = writeln!(h, "#ifndef {n}_EXPORT_H\n#define {n}_EXPORT_H\n");
+    if build.kind == ProjKind::StaticLib {
+        let _ = writeln!(h, "#ifndef {n}_STATIC_DEFINE\n#  define {n}_STATIC_DEFINE\n#endif\n");
+    }
+    let _ = writeln!(
+        h,
+        "\
+#ifdef {n}_STATIC_DEFINE
+#  define {n}_API
+#  define {n}_NO_EXPORT
+#else
+#  ifndef {n}_API
+#    if defined(_WIN32) || defined(__CYGWIN__)
+#      ifdef {n}_EXPORTS
+#        define {n}_API __declspec(dllexport)
+#      else
+#        define {n}_API __declspec(dllimport)
+#      endif
+#    else
+#      define {n}_API __attribute__((visibility(\"default\")))
+#    endif
+#  endif
+#  ifndef {n}_NO_EXPORT
+#    if defined(_WIN32) || defined(__CYGWIN__)
+#      define {n}_NO_EXPORT
+#    else
+#      define {n}_NO_EXPORT __attribute__((visibility(\"hidden\")))
+#    endif
+#  endif
+#endif
+
+#ifndef {n}_DEPRECATED
+#  if defined(_MSC_VER) && !defined(__clang__)
+#    define {n}_DEPRECATED __declspec(deprecated)
+#  else
+#    define {n}_DEPRECATED __attribute__((__deprecated__))
+#  endif
+#endif
+#ifndef {n}_DEPRECATED_EXPORT
+#  define {n}_DEPRECATED_EXPORT {n}_API {n}_DEPRECATED
+#endif
+#ifndef {n}_DEPRECATED_NO_EXPORT
+#  define {n}_DEPRECATED_NO_EXPORT {n}_NO_EXPORT {n}_DEPRECATED
+#endif
+
+#endif /* {n}_EXPORT_H */"
+    );
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VangoFile;
+
+    fn layout() -> Layout {
+        Layout {
+            prefix: PathBuf::from("/home/me/my lib"),
+            libdir: PathBuf::from("bin/debug"),
+            library: PathBuf::from("bin/debug/libmylib.a"),
+            implib: None,
+            pkgconfig_dir: PathBuf::from("bin/debug"),
+            cmake_dir: PathBuf::from("bin/debug"),
+        }
+    }
+
+    fn package() -> (BuildFile, Dependencies) {
+        let manifest = "[package]\nname = \"mylib\"\nversion = \"1.2.3\"\nlang = \"C++20\"\nkind = \"staticlib\"\n\n[dependencies]\n";
+        let build = VangoFile::from_str(manifest).unwrap().unwrap_build();
+        let interface = Dependencies {
+            incdirs: vec!["gen".into(), "/opt/sdl/include".into()],
+            defines: vec!["MYLIB_NAME=\"my lib\"".to_string()],
+            libdirs: vec!["../ylib/bin/debug".into()],
+            archives: vec!["ylib".into(), "m".into()],
+            linker_options: vec!["-pthread".to_string()],
+            ..Default::default()
+        };
+        (build, interface)
+    }
+
+    #[test]
+    fn pkg_config_file() {
+        let (build, interface) = package();
+        let requires = ["sdl2 >= 2.26".to_string(), "zlib".to_string()];
+        assert_eq!(
+            pkg_config(&build, &interface, &requires, &layout()),
+            r#"prefix=/home/me/my\ lib
+includedir=${prefix}/include
+libdir=${prefix}/bin/debug
+
+Name: mylib
+Description: mylib static library
+Version: 1.2.3
+Requires.private: sdl2 >= 2.26, zlib
+Cflags: -I${includedir} -I${prefix}/gen -I/opt/sdl/include -DMYLIB_NAME=\"my\ lib\"
+Libs: -L${libdir} -lmylib
+Libs.private: -L${prefix}/../ylib/bin/debug -lylib -lm -pthread
+"#
+        );
+    }
+
+    #[test]
+    fn cmake_config_file() {
+        let (build, interface) = package();
+        assert_eq!(
+            cmake_config(&build, &interface, &layout()),
+            r#"# generated by vango for mylib 1.2.3
+if(TARGET mylib::mylib)
+  return()
+endif()
+
+get_filename_component(_MYLIB_PREFIX "${CMAKE_CURRENT_LIST_DIR}/../.." ABSOLUTE)
+
+add_library(mylib::mylib STATIC IMPORTED)
+set_target_properties(mylib::mylib PROPERTIES
+  IMPORTED_LOCATION "${_MYLIB_PREFIX}/bin/debug/libmylib.a"
+  IMPORTED_LINK_INTERFACE_LANGUAGES "CXX"
+  INTERFACE_INCLUDE_DIRECTORIES "${_MYLIB_PREFIX}/include;${_MYLIB_PREFIX}/gen;/opt/sdl/include"
+  INTERFACE_COMPILE_DEFINITIONS "MYLIB_NAME=\"my lib\""
+  INTERFACE_LINK_DIRECTORIES "${_MYLIB_PREFIX}/../ylib/bin/debug"
+  INTERFACE_LINK_LIBRARIES "ylib;m"
+  INTERFACE_LINK_OPTIONS "-pthread"
+)
+
+unset(_MYLIB_PREFIX)
+"#
+        );
+
+        let version = cmake_version(&build);
+        assert!(version.contains("set(PACKAGE_VERSION \"1.2.3\")"));
+        assert!(version.contains("NOT PACKAGE_FIND_VERSION_MAJOR STREQUAL \"1\""));
+    }
+
+    #[test]
+    fn export_header_macros() {
+        assert_eq!(macro_prefix("my-lib2"), "MY_LIB2");
+        let (build, _) = package();
+        let h = export_header(&build);
+        assert!(h.contains("#ifndef MYLIB_STATIC_DEFINE\n#  define MYLIB_STATIC_DEFINE"));
+        assert!(h.contains("#        define MYLIB_API __declspec(dllexport)"));
+        assert!(h.contains("#  define MYLIB_DEPRECATED_EXPORT MYLIB_API MYLIB_DEPRECATED"));
+
+        let shared = BuildFile {
+            kind: ProjKind::SharedLib { implib: true },
+            ..build
+        };
+        let h = export_header(&shared);
+        assert!(!h.contains("#  define MYLIB_STATIC_DEFINE"));
+        assert!(h.contains("#      define MYLIB_API __attribute__((visibility(\"default\")))"));
+    }
+}
@@ -15,7 +15,8 @@ pub fn help(action: Option<&String>) {
             "  -t, --toolchain=<TOOL>  Specify a toolchain for compilation (user default: {})",
             ToolChain::default()
         );
-        // println!("      --install           On unix-like systems: installs headers and binaries into /usr/local/* on build");
+        println!("      --locked            Require 'Vango.lock' to be up to date, fail instead of updating it");
+        println!("      --offline           Never access the network, fail if a dependency is not cached or vendored");
         println!("      --echo              Echo the entire build command composed by vango");
         println!("  -v, --verbose           Forward '--verbose' to invoked tool, if available");
         println!();
@@ -60,6 +61,53 @@ pub fn help(action: Option<&String>) {
                 println!();
                 println!("Usage: vango clangd");
             }
+            "update" => {
+                println!("Fetch the newest commits of all (or one) git dependencies and record them in 'Vango.lock'");
+                println!();
+                println!("Usage: vango update [PACKAGE]");
+            }
+            "publish" => {
+                println!("Package the current project and add it to a local registry (a directory or git working copy)");
+                println!();
+                println!("Usage: vango publish --registry <DIR>");
+            }
+            "vendor" => {
+                println!("Copy all git, registry and vcpkg dependencies into 'vendor/' and configure the project to build from it");
+                println!();
+                println!("Usage: vango vendor");
+            }
+            "add" => {
+                println!("Add a dependency to 'Vango.toml' after checking that it is a compatible library, comments and ordering are kept");
+                println!();
+                println!("Usage: vango add <NAME> [OPTIONS]");
+                println!("       vango add <NAME>@<REQ> [OPTIONS]");
+                println!();
+                println!("Options:");
+                println!("        --path <DIR>          Local library");
+                println!("        --git <URL>           Git repository, optionally with one of '--tag', '--branch' or '--rev'");
+                println!("        --headers <DIR>       Local header-only library");
+                println!("        --system              System library of the same name, e.g. 'm' or 'Ws2_32'");
+                println!("        --version <REQ>       Registry package, optionally from '--registry <NAME>'");
+                println!("    -F, --features <LIST>     Comma separated features to enable");
+                println!("        --dev                 Add to '[dev-dependencies]' instead");
+            }
+            "remove" => {
+                println!("Remove a dependency from 'Vango.toml'");
+                println!();
+                println!("Usage: vango remove <NAME> [--dev]");
+            }
+            "tree" => {
+                println!("Print the resolved dependency graph (kind, version, path and features of every package) without building,");
+                println!("dependencies that are not cached yet are fetched like a build would");
+                println!();
+                println!("Usage: vango tree [OPTIONS]");
+                println!();
+                println!("Options:");
+                println!("    -d, --duplicates          Show only packages that appear more than once, and what depends on them");
+                println!("    -i, --invert <PACKAGE>    Show what depends on a package instead of what it depends on");
+                println!("        --format <text|dot>   Print an indented tree (default) or a graphviz digraph");
+                println!("        --offline             Never access the network, fail if a dependency is not cached or vendored");
+            }
             "build" => {
                 println!("Build the current project");
                 println!();
@@ -81,6 +129,34 @@ pub fn help(action: Option<&String>) {
                 println!();
                 print_build_details();
             }
+            "install" => {
+                println!("Build the current project in release profile and copy it into a prefix (binaries, headers, package files)");
+                println!();
+                println!("Usage: vango install [OPTIONS]");
+                println!();
+                println!("Options:");
+                println!("        --prefix <DIR>          Install into <DIR> (default: /usr/local, or 'Program Files' on windows)");
+                println!("        --destdir <DIR>         Stage the install below <DIR> without changing the prefix, i.e. for packaging");
+                println!("    -t, --toolchain=<TOOL>      Specify a toolchain for compilation");
+                println!("        --locked                Require 'Vango.lock' to be up to date, fail instead of updating it");
+                println!("        --offline               Never access the network, fail if a dependency is not cached or vendored");
+            }
+            "package" => {
+                println!("Build a library in several profiles and bundle it as a prebuilt '[staticlib]' package with checksums");
+                println!();
+                println!("Usage: vango package [OPTIONS]");
+                println!();
+                println!("Options:");
+                println!("        --profiles <LIST>       Comma separated profiles to bundle (default: debug,release)");
+                println!("    -t, --toolchain=<TOOL>      Specify a toolchain for compilation, each toolchain is a separate bundle");
+                println!("        --locked                Require 'Vango.lock' to be up to date, fail instead of updating it");
+                println!("        --offline               Never access the network, fail if a dependency is not cached or vendored");
+            }
+            "uninstall" => {
+                println!("Remove exactly the files recorded by the last 'vango install' of the current project");
+                println!();
+                println!("Usage: vango uninstall [--prefix <DIR>] [--destdir <DIR>]");
+            }
             "toolchains" => {
                 println!("Toolchains currently installed on this system:");
                 println!();
@@ -130,6 +206,15 @@ pub fn help(action: Option<&String>) {
         println!("    run,   r    Build the current project and run it");
         println!("    test,  t    Build the current project and test it");
         println!("    clangd      Generate 'compile_flags.txt' for the current project");
+        println!("    update      Update locked git dependencies in 'Vango.lock'");
+        println!("    vendor      Copy all remote dependencies into 'vendor/' for offline builds");
+        println!("    publish     Package the current project and add it to a registry");
+        println!("    add         Add a dependency to the manifest");
+        println!("    remove, rm  Remove a dependency from the manifest");
+        println!("    tree        Display the dependency graph of the current project");
+        println!("    install     Build the current project in release profile and install it into a prefix");
+        println!("    uninstall   Remove the files installed by 'install'");
+        println!("    package     Bundle a library as a distributable prebuilt package");
     }
     println!();
 }
@@ -0,0 +1,290 @@
+use super::export;
+use crate::{
+    config::{BuildFile, ProjKind, ToolChain},
+    error::Error,
+    exec,
+    fetch::{Dependencies, Resolver},
+    input::BuildSwitches,
+    log_info_ln,
+};
+use std::path::{Component, Path, PathBuf};
+
+const MANIFEST: &str = "install-manifest.txt";
+
+/// builds the release profile and copies the project into a prefix, every installed file is recorded in a manifest
+/// ('share/vango/<name>/install-manifest.txt') that 'uninstall' removes exactly, a previous install is replaced
+pub fn install(build: &BuildFile, switches: &BuildSwitches, prefix: Option<&Path>, destdir: Option<&Path>) -> Result<(), Error> {
+    let prefix = install_prefix(build, prefix)?;
+    let root = staged(&prefix, destdir);
+
+    let mut resolver = Resolver::new(build, switches.locked, switches.offline)?;
+    let interface = super::build(build, switches, &mut resolver, false)?;
+    resolver.finish()?;
+
+    if root.join(manifest_path(build)).exists() {
+        remove_installed(build, &root)?;
+    }
+    log_info_ln!("{:=<80}", format!("installing '{}' into: {} ", build.name, root.display()));
+    let mut installed = Vec::new();
+    let (_, outfile, implib) = super::build::output_files(build, switches);
+    let filename = PathBuf::from(outfile.file_name().unwrap());
+    match build.kind {
+        ProjKind::App => installed.push(copy(&outfile, &root, &Path::new("bin").join(filename))?),
+        ProjKind::StaticLib => {
+            installed.push(copy_archive(&outfile, &root, &Path::new("lib").join(filename), switches.toolchain)?);
+            // archives of vango dependencies are linked by consumers of the archive, so they are installed with it
+            for archive in &interface.relink {
+                let dest = Path::new("lib").join(archive.file_name().unwrap());
+                installed.push(copy_archive(archive, &root, &dest, switches.toolchain)?);
+            }
+        }
+        ProjKind::SharedLib { .. } if cfg!(windows) => {
+            // DLLs are found next to executables or on the PATH, import libraries are linked
+            installed.push(copy(&outfile, &root, &Path::new("bin").join(filename))?);
+            if let Some(implib) = &implib {
+                installed.push(copy(implib, &root, &Path::new("lib").join(implib.file_name().unwrap()))?);
+            }
+        }
+        ProjKind::SharedLib { .. } => installed.extend(install_shared(build, switches, &outfile, &root)?),
+    }
+    if matches!(build.kind, ProjKind::App | ProjKind::SharedLib { .. }) {
+        // shared libraries of dependencies are loaded from the prefix, not from the tree they were built in
+        let dir = if cfg!(windows) { Path::new("bin") } else { Path::new("lib") };
+        for lib in &interface.shared_libs {
+            installed.push(copy_shared_dependency(lib, &root, &dir.join(lib.file_name().unwrap()))?);
+        }
+    }
+
+    if build.kind.is_lib() {
+        // public include directories of the project are merged into the installed 'include' tree
+        let profile = build.get(&switches.profile)?;
+        let mut includes = vec![PathBuf::from("include")];
+        includes.extend(profile.public_include.iter().filter(|i| i.is_relative() && i.is_dir()).cloned());
+        includes.push(PathBuf::from(export::HEADER_DIR));
+        for dir in includes {
+            for file in files(&dir)? {
+                let dest = Path::new("include").join(file.strip_prefix(&dir).unwrap());
+                installed.push(copy(&file, &root, &dest)?);
+            }
+        }
+
+        // paths of the project itself now live in the prefix, those of its dependencies stay where they are
+        let base = std::env::current_dir()?;
+        let own: Vec<_> = profile
+            .public_include
+            .iter()
+            .filter(|i| i.is_relative())
+            .map(PathBuf::as_path)
+            .chain([Path::new(export::HEADER_DIR)])
+            .collect();
+        let incdirs = interface.incdirs.iter().filter(|i| !own.contains(&i.as_path()));
+        let interface = Dependencies {
+            incdirs: incdirs.map(|i| base.join(i)).collect(),
+            libdirs: interface.libdirs.iter().map(|l| base.join(l)).collect(),
+            rpaths: interface.rpaths.iter().map(|r| base.join(r)).collect(),
+            ..interface
+        };
+        let library = if cfg!(windows) && build.kind != ProjKind::StaticLib {
+            Path::new("bin").join(outfile.file_name().unwrap())
+        } else {
+            Path::new("lib").join(outfile.file_name().unwrap())
+        };
+        let layout = export::Layout {
+            prefix: prefix.clone(),
+            libdir: PathBuf::from("lib"),
+            library,
+            implib: implib
+                .filter(|_| cfg!(windows))
+                .map(|i| Path::new("lib").join(i.file_name().unwrap())),
+            pkgconfig_dir: Path::new("lib").join("pkgconfig"),
+            cmake_dir: Path::new("lib").join("cmake").join(&build.name),
+        };
+        let requires = interface.requires.clone();
+        let mut generated = export::write(build, &interface, &requires, &layout, &root)?;
+        generated.push(write_lib_manifest(build, &interface, switches.toolchain, &root)?);
+        for file in generated {
+            log_info_ln!("installing: {}", root.join(&file).display());
+            installed.push(file);
+        }
+    }
+
+    let manifest = manifest_path(build);
+    std::fs::create_dir_all(root.join(&manifest).parent().unwrap())?;
+    installed.push(manifest.clone());
+    let list: String = installed
+        .iter()
+        .map(|f| format!("{}\n", f.to_string_lossy().replace('\\', "/")))
+        .collect();
+    std::fs::write(root.join(&manifest), list)?;
+    log_info_ln!(
+        "installed {} files, recorded in: {}",
+        installed.len(),
+        root.join(manifest).display()
+    );
+    Ok(())
+}
+
+/// removes every file recorded by 'install', and the directories that are left empty (but never those directly in the prefix)
+pub fn uninstall(build: &BuildFile, prefix: Option<&Path>, destdir: Option<&Path>) -> Result<(), Error> {
+    let prefix = install_prefix(build, prefix)?;
+    let root = staged(&prefix, destdir);
+    if !root.join(manifest_path(build)).exists() {
+        return Err(Error::NotInstalled(build.name.clone(), root));
+    }
+    log_info_ln!("{:=<80}", format!("uninstalling '{}' from: {} ", build.name, root.display()));
+    let count = remove_installed(build, &root)?;
+    log_info_ln!("removed {count} files");
+    Ok(())
+}
+
+/// '--prefix' made absolute, otherwise '/usr/local' (or 'C:\Program Files\<name>'), as in CMake
+fn install_prefix(build: &BuildFile, prefix: Option<&Path>) -> Result<PathBuf, Error> {
+    match prefix {
+        Some(prefix) => Ok(std::path::absolute(prefix)?),
+        None if cfg!(windows) => Ok(PathBuf::from("C:\\Program Files").join(&build.name)),
+        None => Ok(PathBuf::from("/usr/local")),
+    }
+}
+
+/// where files for 'prefix' are written, '--destdir' stages an install (i.e. for packaging) without changing the prefix
+fn staged(prefix: &Path, destdir: Option<&Path>) -> PathBuf {
+    match destdir {
+        Some(destdir) => destdir.join(
+            prefix
+                .components()
+                .filter(|c| matches!(c, Component::Normal(_)))
+                .collect::<PathBuf>(),
+        ),
+        None => prefix.to_path_buf(),
+    }
+}
+
+fn manifest_path(build: &BuildFile) -> PathBuf {
+    Path::new("share").join("vango").join(&build.name).join(MANIFEST)
+}
+
+fn remove_installed(build: &BuildFile, root: &Path) -> Result<usize, Error> {
+    let list = std::fs::read_to_string(root.join(manifest_path(build)))?;
+    let mut count = 0;
+    for file in list.lines().filter(|l| !l.is_empty()).map(|l| root.join(l)) {
+        match std::fs::remove_file(&file) {
+            Ok(()) => count += 1,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e.into()),
+        }
+        let mut dir = file.parent();
+        while let Some(d) = dir
+            && d.strip_prefix(root).is_ok_and(|r| r.components().count() > 1)
+            && std::fs::remove_dir(d).is_ok()
+        {
+            dir = d.parent();
+        }
+    }
+    Ok(count)
+}
+
+/// copies a file to 'dest' (relative to 'root'), replacing what is there rather than writing into it
+fn copy(src: &Path, root: &Path, dest: &Path) -> Result<PathBuf, Error> {
+    let target = root.join(dest);
+    std::fs::create_dir_all(target.parent().unwrap())?;
+    let _ = std::fs::remove_file(&target);
+    std::fs::copy(src, &target)?;
+    log_info_ln!("installing: {}", target.display());
+    Ok(dest.to_path_buf())
+}
+
+/// copies a static library like 'copy', thin archives are installed as regular ones
+fn copy_archive(src: &Path, root: &Path, dest: &Path, toolchain: ToolChain) -> Result<PathBuf, Error> {
+    let target = root.join(dest);
+    std::fs::create_dir_all(target.parent().unwrap())?;
+    exec::copy_archive(src, &target, toolchain)?;
+    log_info_ln!("installing: {}", target.display());
+    Ok(dest.to_path_buf())
+}
+
+/// installs the versioned file of a shared library with its soname and the link used to link against it
+/// ('libname.so.1.2.3', 'libname.so.1' and 'libname.so')
+fn install_shared(build: &BuildFile, switches: &BuildSwitches, outfile: &Path, root: &Path) -> Result<Vec<PathBuf>, Error> {
+    let lib = Path::new("lib");
+    let Some((file, soname)) = exec::versioned_shared_lib(outfile, build.version, switches.toolchain) else {
+        return Ok(vec![copy(outfile, root, &lib.join(outfile.file_name().unwrap()))?]);
+    };
+    let mut installed = vec![copy(&file, root, &lib.join(file.file_name().unwrap()))?];
+    for (target, link) in [(&file, &soname), (&soname, &outfile.to_path_buf())] {
+        let target = root.join(lib).join(target.file_name().unwrap());
+        let link = lib.join(link.file_name().unwrap());
+        exec::relink_shared_lib(&target, &root.join(&link))?;
+        log_info_ln!(
+            "installing: {} -> {}",
+            root.join(&link).display(),
+            target.file_name().unwrap().to_string_lossy()
+        );
+        installed.push(link);
+    }
+    Ok(installed)
+}
+
+/// copies a shared library like 'copy', its soname links are installed as links to the same file
+fn copy_shared_dependency(src: &Path, root: &Path, dest: &Path) -> Result<PathBuf, Error> {
+    if !std::fs::symlink_metadata(src)?.is_symlink() {
+        return copy(src, root, dest);
+    }
+    let target = root.join(dest).with_file_name(std::fs::read_link(src)?.file_name().unwrap());
+    std::fs::create_dir_all(target.parent().unwrap())?;
+    exec::relink_shared_lib(&target, &root.join(dest))?;
+    log_info_ln!(
+        "installing: {} -> {}",
+        root.join(dest).display(),
+        target.file_name().unwrap().to_string_lossy()
+    );
+    Ok(dest.to_path_buf())
+}
+
+/// a '[staticlib]' manifest next to the install manifest, so that vango projects can depend on the installed library
+/// ('mylib = { path="/usr/local/share/vango/mylib" }')
+fn write_lib_manifest(build: &BuildFile, interface: &Dependencies, toolchain: ToolChain, root: &Path) -> Result<PathBuf, Error> {
+    let string = |s: &str| toml::Value::String(s.to_string()).to_string();
+    let list = |items: Vec<String>| format!("[{}]", items.iter().map(|i| string(i)).collect::<Vec<_>>().join(", "));
+    let mut binaries = Vec::new();
+    if build.kind.has_lib() {
+        binaries.push(build.name.clone());
+    }
+    binaries.extend(interface.archives.iter().map(|a| {
+        let a = a.to_string_lossy();
+        if toolchain.is_msvc() {
+            a.strip_suffix(".lib").unwrap_or(&a).to_string()
+        } else {
+            a.to_string()
+        }
+    }));
+    let manifest = format!(
+        "# generated by 'vango install', removed by 'vango uninstall'\n[staticlib]\nname = {}\nversion = {}\nlang = {}\ninclude = \"../../../include\"\nlibdir = \"../../../lib\"\nbinaries = {}\ndefines = {}\n",
+        string(&build.name),
+        string(&build.version.to_string()),
+        string(&build.interface.to_string()),
+        list(binaries),
+        list(interface.defines.clone()),
+    );
+    let path = manifest_path(build).with_file_name("Vango.toml");
+    std::fs::create_dir_all(root.join(&path).parent().unwrap())?;
+    std::fs::write(root.join(&path), manifest)?;
+    Ok(path)
+}
+
+/// every file below a directory, recursively
+pub(super) fn files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut list = Vec::new();
+    if !dir.is_dir() {
+        return Ok(list);
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list.extend(files(&path)?);
+        } else {
+            list.push(path);
+        }
+    }
+    list.sort();
+    Ok(list)
+}
@@ -1,17 +1,32 @@
+mod add;
 mod build;
 mod clangd;
+mod export;
 mod help;
+mod install;
 mod new;
+mod package;
+mod publish;
 mod run;
 mod test;
+mod tree;
+mod update;
+mod vendor;
 
 use crate::{config::BuildFile, error::Error, log_info_ln};
+pub use add::{add, remove};
 pub use build::build;
 pub use clangd::clangd;
 pub use help::{help, version};
+pub use install::{install, uninstall};
 pub use new::{init, new};
+pub use package::package;
+pub use publish::publish;
 pub use run::run;
 pub use test::test;
+pub use tree::tree;
+pub use update::update;
+pub use vendor::vendor;
 
 pub fn clean(build: &BuildFile) -> Result<(), Error> {
     log_info_ln!("cleaning build files for \"{}\"", build.name);
@@ -0,0 +1,149 @@
+use crate::{
+    config::{BuildFile, LibFile, Profile, ProjKind},
+    error::Error,
+    exec,
+    fetch::{self, Resolver, archive},
+    input::BuildSwitches,
+    log_info_ln,
+};
+use std::path::{Path, PathBuf};
+
+/// builds a library in every requested profile and bundles it as a prebuilt '[staticlib]' package, the bundle
+/// ('bin/package/<name>-<version>-<toolchain>.tar.gz') unpacks into a directory that can be used as a dependency as is
+pub fn package(build: &BuildFile, switches: &BuildSwitches, profiles: &[Profile]) -> Result<(), Error> {
+    if !build.kind.is_lib() {
+        return Err(Error::PackageNotLib(build.name.clone()));
+    }
+    let bundle = format!("{}-{}-{}", build.name, build.version, switches.toolchain.as_directory());
+    let root = Path::new("bin").join("package").join(&bundle);
+
+    let mut resolver = Resolver::new(build, switches.locked, switches.offline)?;
+    let mut built = Vec::new();
+    for profile in profiles {
+        let switches = BuildSwitches {
+            profile: profile.clone(),
+            ..switches.clone()
+        };
+        let interface = super::build(build, &switches, &mut resolver, false)?;
+        built.push((switches, interface));
+    }
+    resolver.finish()?;
+
+    log_info_ln!("{:=<80}", format!("packaging '{}' v{} ", build.name, build.version));
+    if root.exists() {
+        std::fs::remove_dir_all(&root)?;
+    }
+    std::fs::create_dir_all(&root)?;
+
+    // consumers receive the include directory, and the relative public include directories of every profile
+    let mut includes = vec![PathBuf::from("include"), PathBuf::from(super::export::HEADER_DIR)];
+    for (switches, _) in &built {
+        for dir in &build.get(&switches.profile)?.public_include {
+            if dir.is_relative() && dir.is_dir() && !includes.contains(dir) {
+                includes.push(dir.clone());
+            }
+        }
+    }
+    for dir in includes.iter().filter(|i| i.is_dir()) {
+        fetch::copy_dir_all(dir, &root.join("include"))?;
+    }
+
+    let library = LibFile::from_build(build.clone(), switches.toolchain)?;
+    let mut manifest = toml::Table::new();
+    let mut package = toml::Table::new();
+    package.insert("name".into(), build.name.clone().into());
+    package.insert("version".into(), build.version.to_string().into());
+    package.insert("lang".into(), build.interface.to_string().into());
+    package.insert("include".into(), "include".into());
+    manifest.insert("staticlib".into(), package.into());
+
+    let mut tables = toml::Table::new();
+    for (switches, interface) in built {
+        let name = switches.profile.to_string();
+        let libdir = Path::new("lib").join(&name);
+        std::fs::create_dir_all(root.join(&libdir))?;
+
+        // everything that is linked goes into the profile's libdir, archives of vango dependencies included
+        let (_, outfile, implib) = super::build::output_files(build, &switches);
+        let dest = root.join(&libdir);
+        let versioned = match build.kind {
+            ProjKind::SharedLib { .. } => exec::versioned_shared_lib(&outfile, build.version, switches.toolchain),
+            _ => None,
+        };
+        let mut artefacts = Vec::new();
+        if let Some((file, soname)) = versioned {
+            // shared libraries keep their soname and links, the tarball stores them as links
+            let (file, soname) = (file.file_name().unwrap(), soname.file_name().unwrap());
+            std::fs::copy(outfile.with_file_name(file), dest.join(file))?;
+            exec::relink_shared_lib(&dest.join(file), &dest.join(soname))?;
+            exec::relink_shared_lib(&dest.join(soname), &dest.join(outfile.file_name().unwrap()))?;
+        } else {
+            artefacts.push(outfile);
+        }
+        artefacts.extend(implib.filter(|_| cfg!(windows)));
+        if build.kind == ProjKind::StaticLib {
+            artefacts.extend(interface.relink.iter().cloned());
+        }
+        for file in artefacts {
+            // bundles are unpacked elsewhere, so thin archives are bundled as regular ones
+            if file.extension().is_some_and(|e| e == switches.toolchain.static_lib_ext()) {
+                exec::copy_archive(&file, &dest.join(file.file_name().unwrap()), switches.toolchain)?;
+            } else {
+                std::fs::copy(&file, dest.join(file.file_name().unwrap()))?;
+            }
+        }
+
+        let base = &library.profiles[&name];
+        let mut binaries: Vec<String> = base.binaries.iter().map(|b| b.to_string_lossy().to_string()).collect();
+        binaries.extend(interface.archives.iter().map(|a| {
+            let a = a.to_string_lossy();
+            if switches.toolchain.is_msvc() {
+                a.strip_suffix(".lib").unwrap_or(&a).to_string()
+            } else {
+                a.to_string()
+            }
+        }));
+        // builtin profile definitions are added by the consumer, according to the inherited profile
+        let mut defines: Vec<String> = Vec::new();
+        for d in base.defines.iter().chain(&interface.defines) {
+            if d != "VANGO_DEBUG" && d != "VANGO_RELEASE" && !defines.contains(d) {
+                defines.push(d.clone());
+            }
+        }
+
+        let mut table = toml::Table::new();
+        if let Profile::Custom(_) = switches.profile {
+            table.insert("inherits".into(), build.get(&switches.profile)?.baseprof.to_string().into());
+        }
+        table.insert("libdir".into(), libdir.to_string_lossy().replace('\\', "/").into());
+        table.insert("binaries".into(), binaries.into());
+        table.insert("defines".into(), defines.into());
+        tables.insert(name, table.into());
+    }
+    manifest.insert("profile".into(), tables.into());
+    let text = toml::to_string(&manifest).unwrap();
+    std::fs::write(root.join("Vango.toml"), format!("# generated by 'vango package'\n{text}"))?;
+
+    // checksums of the bundled files, in the format of 'sha256sum --check'
+    let mut sums = String::new();
+    for file in super::install::files(&root)? {
+        let rel = file.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+        sums.push_str(&format!("{}  {rel}\n", archive::sha256_hex(&std::fs::read(&file)?)));
+    }
+    std::fs::write(root.join("SHA256SUMS"), sums)?;
+
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    builder.follow_symlinks(false);
+    builder.append_dir_all(&bundle, &root)?;
+    let data = builder.into_inner()?.finish()?;
+    let tarball = root.with_file_name(format!("{bundle}.tar.gz"));
+    let sha256 = archive::sha256_hex(&data);
+    std::fs::write(&tarball, &data)?;
+    std::fs::write(
+        root.with_file_name(format!("{bundle}.tar.gz.sha256")),
+        format!("{sha256}  {}\n", tarball.file_name().unwrap().to_string_lossy()),
+    )?;
+    log_info_ln!("packaged '{}' v{} to: {}", build.name, build.version, tarball.display());
+    log_info_ln!("sha256: {sha256}");
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use crate::{
+    config::{BuildFile, Dependency},
+    error::Error,
+    fetch::{
+        archive, git,
+        registry::{self, IndexEntry, IndexFile},
+    },
+    log_info_ln,
+};
+use std::path::{Path, PathBuf};
+
+/// packages the current project and adds it to the index of a local registry directory
+pub fn publish(build: &BuildFile, registry: &Path) -> Result<(), Error> {
+    // local paths can not be resolved by consumers of the package
+    if let Some((name, _)) = build.dependencies.iter().find(|(_, d)| match d {
+        Dependency::Package { src, .. } => src != "vcpkg",
+        Dependency::Headers { .. } => true,
+        Dependency::Archive { archive, .. } => !archive::is_remote(archive),
+        Dependency::Git { git, .. } => !archive::is_remote(git),
+        _ => false,
+    }) {
+        return Err(Error::PublishLocalDependency(name.clone()));
+    }
+
+    let mut index = IndexFile::load(registry, &build.name)?;
+    if index.get(build.version).is_some() {
+        return Err(Error::AlreadyPublished(build.name.clone(), build.version));
+    }
+
+    log_info_ln!("packaging '{}' v{}", build.name, build.version);
+    let data = registry::pack(&std::env::current_dir()?)?;
+    let location = PathBuf::from(registry::PACKAGE_DIR)
+        .join(&build.name)
+        .join(format!("{}-{}.tar.gz", build.name, build.version));
+    std::fs::create_dir_all(registry.join(&location).parent().unwrap())?;
+    std::fs::write(registry.join(&location), &data)?;
+
+    index.versions.push(IndexEntry {
+        version: build.version.to_string(),
+        // index entries always use forward slashes, registries are shared across platforms
+        archive: location.to_string_lossy().replace('\\', "/"),
+        sha256: archive::sha256_hex(&data),
+    });
+    index.save(registry, &build.name)?;
+
+    if registry.join(".git").exists() {
+        git::commit_all(registry, &format!("publish {} {}", build.name, build.version))?;
+        log_info_ln!("committed to the registry repository, push it to make the release available");
+    }
+    log_info_ln!("published '{}' v{} to: {}", build.name, build.version, registry.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VangoFile;
+
+    #[test]
+    fn rejects_local_sources() {
+        let header = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nlang = \"C++20\"\n\n[dependencies]\n";
+        for dep in [
+            "z = { archive=\"../z.tar.gz\", sha256=\"0000000000000000000000000000000000000000000000000000000000000000\" }",
+            "z = { archive=\"file:///z.tar.gz\", sha256=\"0000000000000000000000000000000000000000000000000000000000000000\" }",
+            "z = { git=\"../z\", branch=\"main\" }",
+        ] {
+            let build = VangoFile::from_str(&format!("{header}{dep}\n")).unwrap().unwrap_build();
+            assert!(matches!(publish(&build, Path::new("missing")), Err(Error::PublishLocalDependency(n)) if n == "z"));
+        }
+    }
+}
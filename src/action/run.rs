@@ -1,4 +1,4 @@
-use crate::{config::ToolChain, error::Error, input::BuildSwitches, log_info_ln};
+use crate::{config::ToolChain, error::Error, exec, input::BuildSwitches, log_info_ln};
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 use std::{
@@ -6,7 +6,8 @@ use std::{
     process::{ExitCode, ExitStatus},
 };
 
-pub fn run(name: &str, switches: &BuildSwitches, runargs: Vec<String>) -> Result<ExitCode, Error> {
+/// runs an application, with the shared libraries of its dependencies ('shared_libs') on the loader path
+pub fn run(name: &str, switches: &BuildSwitches, shared_libs: &[PathBuf], runargs: Vec<String>) -> Result<ExitCode, Error> {
     let outdir = if switches.toolchain == ToolChain::system_default() {
         PathBuf::from("bin").join(switches.profile.to_string())
     } else {
@@ -17,7 +18,11 @@ pub fn run(name: &str, switches: &BuildSwitches, runargs: Vec<String>) -> Result
     let outfile = outdir.join(name).with_extension(switches.toolchain.app_ext());
 
     log_info_ln!("{:=<80}", format!("running application: {} ", outfile.display()));
-    let status = std::process::Command::new(PathBuf::from(".").join(&outfile))
+    let mut cmd = std::process::Command::new(PathBuf::from(".").join(&outfile));
+    if let Some((var, value)) = exec::runtime_env(shared_libs) {
+        cmd.env(var, value);
+    }
+    let status = cmd
         .args(runargs)
         .current_dir(std::env::current_dir().unwrap())
         .status()
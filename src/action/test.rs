@@ -1,8 +1,9 @@
+use super::export;
 use crate::{
     Error,
-    config::{BuildFile, ToolChain},
+    config::{BuildFile, BuildProfile, ToolChain},
     exec::{self, BuildInfo, prep},
-    fetch,
+    fetch::{self, Dependencies, Resolver},
     input::BuildSwitches,
     log_info_ln,
 };
@@ -11,14 +12,13 @@ use std::{
     process::ExitCode,
 };
 
-pub fn test(mut build: BuildFile, switches: &BuildSwitches, args: Vec<String>) -> Result<ExitCode, Error> {
+pub fn test(mut build: BuildFile, switches: &BuildSwitches, resolver: &mut Resolver, args: Vec<String>) -> Result<ExitCode, Error> {
     if !std::fs::exists("test").unwrap_or_default() {
         return Err(Error::MissingTests(build.name));
     }
 
     let include = std::env::current_exe()?.parent().unwrap().to_owned().join("testframework");
 
-    let profile = build.take(&switches.profile)?;
     let mut headers = fetch::source_files(Path::new("include"), "h")?;
     headers.extend(fetch::source_files(Path::new("include"), "hpp")?);
     headers.extend(fetch::source_files(Path::new("src"), "h")?);
@@ -28,13 +28,17 @@ pub fn test(mut build: BuildFile, switches: &BuildSwitches, args: Vec<String>) -
     headers.push(include.join("vangotest/asserts2.h"));
     headers.push(include.join("vangotest/casserts2.h"));
 
-    let mut inherited = fetch::libraries(&build, &profile.baseprof, switches)?;
+    let (mut inherited, profile) = dependencies(&mut build, switches, resolver)?;
     inherited.defines.push("VANGO_TEST".to_string());
     if cfg!(windows) {
         inherited.defines.push("UNICODE".to_string());
         inherited.defines.push("_UNICODE".to_string());
     }
     inherited.incdirs.extend(["test".into(), include, "src".into(), "include".into()]);
+    inherited.incdirs.push(export::HEADER_DIR.into());
+    // tests consume the library like any other project would
+    inherited.incdirs.extend(profile.public_include.iter().cloned());
+    inherited.defines.extend(profile.public_defines.iter().cloned());
 
     let base_outdir = if switches.toolchain == ToolChain::system_default() {
         PathBuf::from("bin").join(switches.profile.to_string())
@@ -44,6 +48,9 @@ pub fn test(mut build: BuildFile, switches: &BuildSwitches, args: Vec<String>) -
             .join(switches.profile.to_string())
     };
     inherited.libdirs.push(base_outdir.clone());
+    if let crate::config::ProjKind::SharedLib { .. } = build.kind {
+        inherited.shared_libs.push(super::build::output_files(&build, switches).1);
+    }
 
     let outdir = base_outdir.join("test");
     let outfile = outdir.join(format!("test_{}.exe", build.name));
@@ -81,15 +88,21 @@ pub fn test(mut build: BuildFile, switches: &BuildSwitches, args: Vec<String>) -
         headers,
         archives: inherited.archives,
         relink,
+        shared_libs: inherited.shared_libs.clone(),
         outfile: outfile.clone(),
         implib: None,
+        version: build.version,
 
         comp_args: vec![],
         link_args: vec![],
     };
     exec::run_build(info, switches.echo, false, false)?;
     log_info_ln!("{:=<80}", format!("running tests for project: {} ", build.name));
-    let status = std::process::Command::new(PathBuf::from(".").join(&outfile))
+    let mut cmd = std::process::Command::new(PathBuf::from(".").join(&outfile));
+    if let Some((var, value)) = exec::runtime_env(&inherited.shared_libs) {
+        cmd.env(var, value);
+    }
+    let status = cmd
         .args(args)
         .current_dir(std::env::current_dir().unwrap())
         .status()
@@ -97,3 +110,37 @@ pub fn test(mut build: BuildFile, switches: &BuildSwitches, args: Vec<String>) -
 
     crate::action::run::graceful_crash(outfile, status)
 }
+
+/// the dependencies of the tests and the profile they are built with, dependencies are collected with the settings
+/// of the profile, so before it is taken from the manifest
+fn dependencies(build: &mut BuildFile, switches: &BuildSwitches, resolver: &mut Resolver) -> Result<(Dependencies, BuildProfile), Error> {
+    let baseprof = build.get(&switches.profile)?.baseprof.clone();
+    let deps = fetch::test_libraries(build, &baseprof, switches, resolver)?;
+    Ok((deps, build.take(&switches.profile)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Profile, VangoFile};
+
+    #[test]
+    fn dependencies_before_profile() {
+        let manifest = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nlang = \"C++20\"\n\n[dependencies]\n\n[profile.bench]\ninherits = \"release\"\n";
+        let mut build = VangoFile::from_str(manifest).unwrap().unwrap_build();
+        let mut resolver = Resolver::new(&build, false, true).unwrap();
+        let switches = BuildSwitches {
+            profile: Profile::Custom("bench".to_string()),
+            toolchain: ToolChain::system_default(),
+            locked: false,
+            offline: true,
+            echo: false,
+            verbose: false,
+            is_test: true,
+        };
+        let (deps, profile) = dependencies(&mut build, &switches, &mut resolver).unwrap();
+        assert!(deps.archives.is_empty());
+        assert_eq!(profile.baseprof, Profile::Release);
+        assert!(build.get(&switches.profile).is_err());
+    }
+}
@@ -0,0 +1,392 @@
+use crate::{
+    config::{BuildFile, Dependency, VangoFile, Version},
+    error::Error,
+    fetch::{self, Located, Resolver},
+    input::TreeFormat,
+    log_info_ln,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::{Display, Write},
+    path::PathBuf,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Root,
+    Source,
+    Prebuilt,
+    Recipe,
+    Headers,
+    System,
+    PkgConfig,
+    Vcpkg,
+    Conan,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Root => write!(f, "root"),
+            Self::Source => write!(f, "source"),
+            Self::Prebuilt => write!(f, "prebuilt"),
+            Self::Recipe => write!(f, "recipe"),
+            Self::Headers => write!(f, "headers"),
+            Self::System => write!(f, "system"),
+            Self::PkgConfig => write!(f, "pkg-config"),
+            Self::Vcpkg => write!(f, "vcpkg"),
+            Self::Conan => write!(f, "conan"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    name: String,
+    kind: Kind,
+    version: Option<Version>,
+    path: Option<PathBuf>,
+    /// union of the features requested by every dependent
+    features: Vec<String>,
+}
+
+impl Node {
+    fn label(&self) -> String {
+        let mut label = self.name.clone();
+        if let Some(version) = &self.version {
+            let _ = write!(label, " v{version}");
+        }
+        if self.kind != Kind::Root {
+            let _ = write!(label, " [{}]", self.kind);
+        }
+        if let Some(path) = &self.path {
+            let _ = write!(label, " ({})", path.display());
+        }
+        if !self.features.is_empty() {
+            let _ = write!(label, " features: {}", self.features.join(", "));
+        }
+        label
+    }
+}
+
+/// the resolved package graph, node 0 is the root project
+#[derive(Debug, Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    /// (dependent, dependency, dev-dependency)
+    edges: Vec<(usize, usize, bool)>,
+    /// identity of each package (its canonical root, or kind and name for packages without one)
+    ids: HashMap<String, usize>,
+}
+
+impl Graph {
+    fn new(build: &BuildFile, root: PathBuf) -> Self {
+        let mut graph = Graph::default();
+        graph.ids.insert(root.display().to_string(), 0);
+        graph.nodes.push(Node {
+            name: build.name.clone(),
+            kind: Kind::Root,
+            version: Some(build.version),
+            path: Some(root),
+            features: Vec::new(),
+        });
+        graph
+    }
+
+    /// adds an edge to the package with identity 'id', returns its index if the package is new
+    fn link(&mut self, parent: usize, id: String, node: Node, dev: bool) -> Option<usize> {
+        if let Some(&idx) = self.ids.get(&id) {
+            for feat in node.features {
+                if !self.nodes[idx].features.contains(&feat) {
+                    self.nodes[idx].features.push(feat);
+                }
+            }
+            self.edges.push((parent, idx, dev));
+            return None;
+        }
+        let idx = self.nodes.len();
+        self.nodes.push(node);
+        self.ids.insert(id, idx);
+        self.edges.push((parent, idx, dev));
+        Some(idx)
+    }
+
+    fn neighbours(&self, idx: usize, inverted: bool) -> Vec<(usize, bool)> {
+        self.edges
+            .iter()
+            .filter_map(|&(from, to, dev)| match inverted {
+                false if from == idx => Some((to, dev)),
+                true if to == idx => Some((from, dev)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn render(&self, idx: usize, inverted: bool, out: &mut String) {
+        let _ = writeln!(out, "{}", self.nodes[idx].label());
+        let mut expanded = HashSet::from([idx]);
+        self.render_children(idx, inverted, "", &mut vec![idx], &mut expanded, out);
+    }
+
+    fn render_children(
+        &self,
+        idx: usize,
+        inverted: bool,
+        prefix: &str,
+        stack: &mut Vec<usize>,
+        expanded: &mut HashSet<usize>,
+        out: &mut String,
+    ) {
+        let children = self.neighbours(idx, inverted);
+        for (i, (child, dev)) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let _ = write!(out, "{prefix}{}{}", if last { "└── " } else { "├── " }, self.nodes[*child].label());
+            if *dev {
+                let _ = write!(out, " (dev)");
+            }
+            // subtrees are only printed once, repeats and cycles are marked instead
+            if stack.contains(child) {
+                let _ = writeln!(out, " (cycle)");
+                continue;
+            }
+            if !expanded.insert(*child) {
+                let _ = writeln!(out, "{}", if self.neighbours(*child, inverted).is_empty() { "" } else { " (*)" });
+                continue;
+            }
+            let _ = writeln!(out);
+            stack.push(*child);
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            self.render_children(*child, inverted, &prefix, stack, expanded, out);
+            stack.pop();
+        }
+    }
+
+    /// graphviz output of the nodes in 'keep' (all nodes if empty)
+    fn render_dot(&self, keep: &HashSet<usize>, out: &mut String) {
+        let _ = writeln!(out, "digraph dependencies {{");
+        for (i, node) in self.nodes.iter().enumerate() {
+            if keep.is_empty() || keep.contains(&i) {
+                let _ = writeln!(
+                    out,
+                    "    n{i} [label=\"{}\"];",
+                    node.label().replace('\\', "\\\\").replace('"', "\\\"")
+                );
+            }
+        }
+        for &(from, to, dev) in &self.edges {
+            if keep.is_empty() || (keep.contains(&from) && keep.contains(&to)) {
+                let _ = writeln!(out, "    n{from} -> n{to}{};", if dev { " [style=dashed]" } else { "" });
+            }
+        }
+        let _ = writeln!(out, "}}");
+    }
+
+    /// 'targets' and everything that depends on them
+    fn dependents(&self, targets: &[usize]) -> HashSet<usize> {
+        let mut keep = HashSet::new();
+        let mut queue = targets.to_vec();
+        while let Some(idx) = queue.pop() {
+            if keep.insert(idx) {
+                queue.extend(self.neighbours(idx, true).into_iter().map(|(n, _)| n));
+            }
+        }
+        keep
+    }
+}
+
+/// prints the resolved dependency graph of the project without building anything, but dependencies that are not
+/// cached yet are fetched (cloned, downloaded or checked out) like for a build, unless 'offline'
+pub fn tree(build: &BuildFile, duplicates: bool, invert: Option<&str>, format: TreeFormat, offline: bool) -> Result<(), Error> {
+    // lock changes are only ever persisted by builds
+    let mut resolver = Resolver::new(build, false, offline)?;
+    // packages are identified by their canonical root, so a dependency on the root project is the root node
+    let mut graph = Graph::new(build, std::fs::canonicalize(resolver.root())?);
+    let deps = build.dependencies.iter().map(|d| (d, false));
+    let dev = build.dev_dependencies.iter().map(|d| (d, true));
+    let save = std::env::current_dir()?;
+    let result = walk(&mut graph, 0, deps.chain(dev).collect(), &mut resolver);
+    std::env::set_current_dir(&save)?;
+    result?;
+
+    let targets: Vec<usize> = if let Some(name) = invert {
+        let found: Vec<usize> = (0..graph.nodes.len()).filter(|i| graph.nodes[*i].name == name).collect();
+        if found.is_empty() {
+            return Err(Error::NotInGraph(name.to_string()));
+        }
+        found
+    } else if duplicates {
+        let mut names: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (i, node) in graph.nodes.iter().enumerate() {
+            names.entry(&node.name).or_default().push(i);
+        }
+        let found: Vec<usize> = names.into_values().filter(|n| n.len() > 1).flatten().collect();
+        if found.is_empty() {
+            log_info_ln!("no package appears more than once in the dependency graph of '{}'", build.name);
+            return Ok(());
+        }
+        found
+    } else {
+        Vec::new()
+    };
+
+    let mut out = String::new();
+    match format {
+        TreeFormat::Dot => graph.render_dot(&graph.dependents(&targets), &mut out),
+        TreeFormat::Text if targets.is_empty() => graph.render(0, false, &mut out),
+        TreeFormat::Text => {
+            for (i, target) in targets.iter().enumerate() {
+                if i != 0 {
+                    let _ = writeln!(out);
+                }
+                graph.render(*target, true, &mut out);
+            }
+        }
+    }
+    print!("{out}");
+    Ok(())
+}
+
+fn walk(graph: &mut Graph, parent: usize, deps: Vec<(&(String, Dependency), bool)>, resolver: &mut Resolver) -> Result<(), Error> {
+    for ((name, dep), dev) in deps {
+        let patched = resolver.patch(name, dep);
+        let dep = patched.as_ref().unwrap_or(dep);
+        let mut node = Node {
+            name: name.clone(),
+            kind: Kind::Source,
+            version: None,
+            path: None,
+            features: dep.features().to_vec(),
+        };
+        let path = match fetch::locate(name, dep, resolver)? {
+            Located::Root { path, .. } => std::fs::canonicalize(&path).map_err(|_| Error::DirectoryNotFound(path))?,
+            Located::Headers(headers) => {
+                node.kind = Kind::Headers;
+                node.path = Some(std::fs::canonicalize(&headers).unwrap_or(headers));
+                graph.link(parent, format!("headers:{}", node.path.as_ref().unwrap().display()), node, dev);
+                continue;
+            }
+            Located::System(lib) => {
+                node.kind = Kind::System;
+                graph.link(parent, format!("system:{}", lib.display()), node, dev);
+                continue;
+            }
+            Located::PkgConfig { module, .. } => {
+                node.kind = Kind::PkgConfig;
+                graph.link(parent, format!("pkg-config:{module}"), node, dev);
+                continue;
+            }
+            Located::Conan(reference) => {
+                // 'name/version@user/channel#revision'
+                let version = reference.split_once('/').map(|(_, v)| v.split(['@', '#']).next().unwrap());
+                node.kind = Kind::Conan;
+                node.version = version.and_then(|v| v.parse().ok());
+                graph.link(parent, format!("conan:{reference}"), node, dev);
+                continue;
+            }
+            Located::Vcpkg => {
+                node.kind = Kind::Vcpkg;
+                graph.link(parent, format!("vcpkg:{name}"), node, dev);
+                continue;
+            }
+        };
+        node.path = Some(path.clone());
+
+        // foreign projects built by recipes have no manifest and thus no dependencies
+        if dep.recipe().is_some() {
+            node.kind = Kind::Recipe;
+            graph.link(parent, path.display().to_string(), node, dev);
+            continue;
+        }
+        let save = std::env::current_dir()?;
+        std::env::set_current_dir(&path)?;
+        let children = match VangoFile::from_str(&crate::read_manifest()?)? {
+            VangoFile::Build(build) => {
+                node.name = build.name.clone();
+                node.version = Some(build.version);
+                Some(build)
+            }
+            VangoFile::Lib(lib) => {
+                node.name = lib.name;
+                node.kind = Kind::Prebuilt;
+                node.version = Some(lib.version);
+                None
+            }
+        };
+        // each package is only expanded once, which also terminates cycles
+        if let Some(idx) = graph.link(parent, path.display().to_string(), node, dev)
+            && let Some(build) = children
+        {
+            walk(graph, idx, build.dependencies.iter().map(|d| (d, false)).collect(), resolver)?;
+        }
+        std::env::set_current_dir(&save)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, kind: Kind) -> Node {
+        Node {
+            name: name.to_string(),
+            kind,
+            version: None,
+            path: None,
+            features: Vec::new(),
+        }
+    }
+
+    #[test]
+    pub fn render_graph() {
+        let mut graph = Graph::default();
+        graph.nodes.push(node("app", Kind::Root));
+        let mid = graph.link(0, "mid".to_string(), node("mid", Kind::Source), false).unwrap();
+        let ylib = graph.link(mid, "ylib".to_string(), node("ylib", Kind::Source), false).unwrap();
+        graph.link(ylib, "z".to_string(), node("z", Kind::System), false);
+        graph.link(0, "ylib".to_string(), node("ylib", Kind::Source), false);
+        graph.link(0, "other/ylib".to_string(), node("ylib", Kind::Headers), true);
+
+        let mut out = String::new();
+        graph.render(0, false, &mut out);
+        assert_eq!(
+            out,
+            "\
+app
+├── mid [source]
+│   └── ylib [source]
+│       └── z [system]
+├── ylib [source] (*)
+└── ylib [headers] (dev)
+"
+        );
+
+        let mut out = String::new();
+        graph.render(ylib, true, &mut out);
+        assert_eq!(
+            out,
+            "\
+ylib [source]
+├── mid [source]
+│   └── app
+└── app
+"
+        );
+
+        let mut out = String::new();
+        graph.render_dot(&graph.dependents(&[ylib]), &mut out);
+        assert_eq!(
+            out,
+            "\
+digraph dependencies {
+    n0 [label=\"app\"];
+    n1 [label=\"mid [source]\"];
+    n2 [label=\"ylib [source]\"];
+    n0 -> n1;
+    n1 -> n2;
+    n0 -> n2;
+}
+"
+        );
+    }
+}
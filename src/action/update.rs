@@ -0,0 +1,74 @@
+use crate::{
+    config::{BuildFile, VangoFile},
+    error::Error,
+    fetch::{self, git, lock},
+    log_info_ln,
+};
+
+pub fn update(build: &BuildFile, package: Option<&str>) -> Result<(), Error> {
+    let root = std::env::current_dir()?;
+    let mut lockfile = lock::LockFile::load(&root)?;
+    let mut found = false;
+
+    // registry packages are unlocked, the next build resolves the newest version matching their requirement
+    let before = lockfile.packages().len();
+    lockfile.retain(|pkg| {
+        let matched = package.is_none_or(|name| name == pkg.name) && pkg.source.starts_with("registry+");
+        if matched {
+            log_info_ln!("updating '{}': {} -> newest matching version on next build", pkg.name, pkg.version);
+        }
+        !matched
+    });
+    let unlocked = lockfile.packages().len() != before;
+    found |= unlocked;
+
+    for pkg in lockfile.packages_mut() {
+        if package.is_some_and(|name| name != pkg.name) {
+            continue;
+        }
+        found = true;
+        let Some((url, reference)) = lock::parse_git_source(&pkg.source) else {
+            continue;
+        };
+
+        let path = fetch::git_cache_path(url, &reference);
+        if !std::fs::exists(&path)? {
+            fetch::pull_git_repo(url, &reference, &path)?;
+        }
+        let rev = git::update(&path, &reference)?;
+        if pkg.rev.as_deref() != Some(rev.as_str()) {
+            log_info_ln!(
+                "updating '{}': {} -> {}",
+                pkg.name,
+                pkg.rev.as_deref().map(short_rev).unwrap_or("none"),
+                short_rev(&rev)
+            );
+        }
+
+        std::env::set_current_dir(&path)?;
+        let manifest = crate::read_manifest().and_then(|m| VangoFile::from_str(&m));
+        std::env::set_current_dir(&root)?;
+        // header-only and subdirectory dependencies have no manifest at the repo root, next build records their version
+        match manifest {
+            Ok(VangoFile::Build(b)) => pkg.version = b.version.to_string(),
+            Ok(VangoFile::Lib(l)) => pkg.version = l.version.to_string(),
+            Err(_) => (),
+        }
+        pkg.rev = Some(rev);
+    }
+
+    if let Some(name) = package
+        && !found
+    {
+        return Err(Error::PackageNotLocked(name.to_string()));
+    }
+    if unlocked || !lockfile.packages().is_empty() {
+        lockfile.save(&root)?;
+    }
+    log_info_ln!("dependencies of '{}' are up to date", build.name);
+    Ok(())
+}
+
+fn short_rev(rev: &str) -> &str {
+    &rev[..rev.len().min(8)]
+}
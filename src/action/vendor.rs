@@ -0,0 +1,160 @@
+use crate::{
+    config::{self, BuildFile, Dependency, VangoFile},
+    error::Error,
+    fetch::{self, Resolver, git, git::GitRef, vcpkg::VcpkgDependency},
+    log_info_ln, log_warn_ln,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+pub const VENDOR_DIR: &str = "vendor";
+
+/// copies every remote (git, registry, archive and vcpkg) dependency of the package graph into 'vendor/' and points the project config at it
+pub fn vendor(build: &BuildFile) -> Result<(), Error> {
+    // tests must build offline as well, but only the root's dev-dependencies are ever built
+    let mut build = build.clone();
+    let dev_dependencies = std::mem::take(&mut build.dev_dependencies);
+    build.dependencies.extend(dev_dependencies);
+    let build = &build;
+
+    let mut resolver = Resolver::new(build, false, false)?;
+    // always start from the package cache, an existing vendor directory is being replaced
+    resolver.ignore_vendor();
+    let root = resolver.root().to_path_buf();
+    let dest = root.join(VENDOR_DIR);
+
+    log_info_ln!("vendoring dependencies of '{}' into: {}", build.name, dest.display());
+    let _ = std::fs::remove_dir_all(&dest);
+    std::fs::create_dir_all(&dest)?;
+
+    let mut visited = HashSet::new();
+    vendor_package(build, &dest, &mut resolver, &mut visited)?;
+    std::env::set_current_dir(&root)?;
+    resolver.finish()?;
+
+    config::write_project_key(&root, "source", "vendor", VENDOR_DIR.into())?;
+    log_info_ln!("vendored sources are used by all future builds (see '{}')", config::CONFIG_FILE);
+    Ok(())
+}
+
+fn vendor_package(build: &BuildFile, dest: &Path, resolver: &mut Resolver, visited: &mut HashSet<PathBuf>) -> Result<(), Error> {
+    let mut vcpkg = Vec::new();
+
+    for (name, dep) in &build.dependencies {
+        let patched = resolver.patch(name, dep);
+        let dep = patched.as_ref().unwrap_or(dep);
+        let path = match dep {
+            Dependency::Git {
+                git,
+                tag,
+                branch,
+                rev,
+                path,
+                headers,
+                ..
+            } => {
+                let reference = GitRef::from_fields(name, rev, branch, tag)?;
+                let (repo, _) = fetch::resolve_git(name, git, &reference, resolver)?;
+                let target = dest.join(fetch::git_vendor_path(git, &reference));
+                if !target.exists() {
+                    log_info_ln!("vendoring: {name}");
+                    copy_tracked(&repo, &target)?;
+                }
+                if headers.is_some() {
+                    continue;
+                }
+                if let Some(path) = path { repo.join(path) } else { repo }
+            }
+            Dependency::Registry { version, registry, .. } => {
+                let (cached, source) = fetch::registry::resolve(name, version, registry.as_deref(), resolver)?;
+                let version = resolver.locked_version(name, &source).expect("registry packages are always locked");
+                let target = dest.join(fetch::registry::vendor_path(name, version));
+                if !target.exists() {
+                    log_info_ln!("vendoring: {name}");
+                    fetch::copy_dir_all(&cached, &target)?;
+                    // packages are built in place, build output is not part of the package
+                    let _ = std::fs::remove_dir_all(target.join("bin"));
+                }
+                cached
+            }
+            Dependency::Archive {
+                archive,
+                sha256,
+                strip_prefix,
+                manifest,
+                headers,
+                ..
+            } => {
+                let cached = fetch::archive::resolve(name, archive, sha256, *strip_prefix, manifest.as_deref(), resolver)?;
+                let injected = manifest.as_deref().map(fetch::archive::read_injected).transpose()?;
+                let key = fetch::archive::vendor_path(archive, &sha256.to_ascii_lowercase(), *strip_prefix, injected.as_deref());
+                let target = dest.join(key);
+                if !target.exists() {
+                    log_info_ln!("vendoring: {name}");
+                    fetch::copy_dir_all(&cached, &target)?;
+                    let _ = std::fs::remove_dir_all(target.join("bin"));
+                }
+                if headers.is_some() {
+                    continue;
+                }
+                cached
+            }
+            Dependency::Package {
+                src, features, version, ..
+            } if src == "vcpkg" => {
+                vcpkg.push(VcpkgDependency::new(name, features, version.as_deref()));
+                continue;
+            }
+            Dependency::Package { src, .. } => src.clone(),
+            Dependency::Headers { .. } | Dependency::System { .. } | Dependency::PkgConfig { .. } => continue,
+            // conan keeps its own cache, which can not be pointed at a directory of the project
+            Dependency::Conan { conan, .. } => {
+                log_warn_ln!("conan package '{conan}' is not vendored, it must already be in the conan cache for offline builds");
+                continue;
+            }
+        };
+
+        // foreign projects built by recipes have no manifest and thus no dependencies
+        if dep.recipe().is_some() {
+            continue;
+        }
+        // dependencies of dependencies are vendored as well, each package is only visited once
+        let path = std::fs::canonicalize(&path).map_err(|_| Error::DirectoryNotFound(path))?;
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        let save = std::env::current_dir()?;
+        std::env::set_current_dir(&path)?;
+        let result = match VangoFile::from_str(&crate::read_manifest()?)? {
+            VangoFile::Build(build) => vendor_package(&build, dest, resolver, visited),
+            VangoFile::Lib(_) => Ok(()),
+        };
+        std::env::set_current_dir(&save)?;
+        result?;
+    }
+
+    if !vcpkg.is_empty() {
+        let installed = fetch::vcpkg::install(&vcpkg, &build.vcpkg)?;
+        fetch::copy_dir_all(&installed, &dest.join("vcpkg_installed"))?;
+    }
+    Ok(())
+}
+
+/// vendored copies contain exactly the checked out sources (no history, no build output)
+fn copy_tracked(repo: &Path, target: &Path) -> Result<(), Error> {
+    for file in git::tracked_files(repo)? {
+        let from = repo.join(&file);
+        // submodule entries that were never initialized
+        if from.is_dir() {
+            continue;
+        }
+        let to = target.join(&file);
+        std::fs::create_dir_all(to.parent().unwrap())?;
+        std::fs::copy(&from, &to)?;
+    }
+    std::fs::create_dir_all(target)?;
+    std::fs::write(target.join(fetch::VENDOR_REV_FILE), git::head(repo)?)?;
+    Ok(())
+}
@@ -1,7 +1,11 @@
-use super::{Lang, Profile, ProjKind, ToolChain, Version};
+use super::{Lang, Profile, ProjKind, ToolChain, Version, VersionReq};
 use crate::error::Error;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BuildFile {
@@ -12,8 +16,14 @@ pub struct BuildFile {
     pub toolchain: Option<ToolChain>,
     pub interface: Lang,
     pub runtime: Option<String>,
+    /// places the shared libraries of dependencies next to the executable (see 'action::build')
+    pub copy_shared: bool,
     pub vcpkg: VcpkgConfig,
     pub dependencies: Vec<(String, Dependency)>,
+    /// only resolved, built and linked for the tests of this package, never for its consumers
+    pub dev_dependencies: Vec<(String, Dependency)>,
+    /// only the patches of the root project apply, those of dependencies are ignored
+    pub patch: Patches,
     pub profiles: HashMap<String, BuildProfile>,
 }
 
@@ -22,6 +32,7 @@ impl BuildFile {
         let mut file: SerdeBuildFile = value.try_into()?;
         let mut profiles: HashMap<String, BuildProfile> = HashMap::new();
         let mut dependencies: Vec<(String, Dependency)> = Vec::new();
+        let mut dev_dependencies: Vec<(String, Dependency)> = Vec::new();
 
         if let Some(d) = file.profile.remove("debug") {
             profiles.insert("debug".to_string(), BuildProfile::debug(&file.package.defaults).merge(d).finish());
@@ -62,7 +73,12 @@ impl BuildFile {
         };
 
         for (k, v) in file.dependencies {
-            dependencies.push((k, v.try_into()?));
+            let dep = Dependency::from_value(&k, v)?;
+            dependencies.push((k, dep));
+        }
+        for (k, v) in file.dev_dependencies {
+            let dep = Dependency::from_value(&k, v)?;
+            dev_dependencies.push((k, dep));
         }
 
         Ok(BuildFile {
@@ -73,8 +89,11 @@ impl BuildFile {
             toolchain,
             interface,
             runtime: file.package.runtime,
-            vcpkg: file.vcpkg.unwrap_or(VcpkgConfig{ triplet: "x64-linux".to_string() }),
+            copy_shared: file.package.copy_shared.unwrap_or(false),
+            vcpkg: file.vcpkg.unwrap_or_default(),
             dependencies,
+            dev_dependencies,
+            patch: file.patch,
             profiles,
         })
     }
@@ -98,35 +117,335 @@ impl BuildFile {
     }
 }
 
+// NOTE: untagged variants are matched in order, so 'Git' and 'Archive' must precede 'Package' ('path' alias) and 'Headers',
+// and anything with a 'version' must precede 'Registry'
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
 pub enum Dependency {
+    Git {
+        git: String,
+        tag: Option<String>,
+        branch: Option<String>,
+        rev: Option<String>,
+        /// subdirectory of the repository that contains the library root
+        path: Option<PathBuf>,
+        /// header-only library, no manifest required
+        headers: Option<PathBuf>,
+        /// how to build a dependency that has no Vango manifest
+        recipe: Option<Recipe>,
+        #[serde(default)]
+        features: Vec<String>,
+    },
+    Archive {
+        /// http(s) url, 'file://' url or local path of a .tar.gz, .tar or .zip archive
+        archive: String,
+        sha256: String,
+        /// number of leading path components removed from every archive entry
+        #[serde(default, rename = "strip-prefix")]
+        strip_prefix: usize,
+        /// manifest (relative to the consuming project) placed in the extracted root, for archives that do not ship one
+        manifest: Option<PathBuf>,
+        /// header-only library, no manifest required
+        headers: Option<PathBuf>,
+        /// how to build a dependency that has no Vango manifest
+        recipe: Option<Recipe>,
+        #[serde(default)]
+        features: Vec<String>,
+    },
     Package {
+        #[serde(alias = "path")]
         src: PathBuf,
         #[serde(default)]
         targets: Vec<PathBuf>,
+        /// how to build a dependency that has no Vango manifest
+        recipe: Option<Recipe>,
         #[serde(default)]
         features: Vec<String>,
+        /// exact version of a vcpkg package, resolved against '[vcpkg] baseline'
+        version: Option<String>,
     },
     Headers {
         headers: PathBuf,
         #[serde(default)]
         features: Vec<String>,
     },
-    Git {
-        git: String,
-        tag: Option<String>,
-        #[serde(default)]
-        features: Vec<String>,
-    },
     System {
         system: PathBuf,
     },
+    PkgConfig {
+        /// module name, as passed to pkg-config
+        #[serde(rename = "pkg-config")]
+        pkg_config: String,
+        /// requirement on the installed version, i.e. '>=2.26' ('2.26' is a minimum)
+        version: Option<String>,
+    },
+    Conan {
+        /// conan reference, i.e. 'zlib/1.3.1' or 'lib/1.0@user/channel'
+        conan: String,
+        /// options of the package, or of any package in its graph if prefixed by a pattern ('openssl/*:no_asm')
+        #[serde(default)]
+        options: BTreeMap<String, ConanOption>,
+    },
+    Registry {
+        /// semver requirement, resolved against the registry index
+        version: String,
+        /// name of a registry in the global config, the default registry if omitted
+        registry: Option<String>,
+        #[serde(default)]
+        features: Vec<String>,
+    },
+}
+
+impl Dependency {
+    /// parses and validates a single dependency, 'fmt = "10.2"' is shorthand for a registry dependency
+    pub fn from_value(name: &str, value: toml::Value) -> Result<Self, Error> {
+        let dep: Dependency = if let toml::Value::String(version) = value {
+            Dependency::Registry {
+                version,
+                registry: None,
+                features: Vec::new(),
+            }
+        } else {
+            value.try_into()?
+        };
+        if let Some(recipe) = dep.recipe() {
+            let t = recipe.table();
+            let tools = [t.cmake.is_some(), t.make.is_some(), t.configure.is_some(), t.script.is_some()];
+            if tools.into_iter().filter(|t| *t).count() != 1 {
+                return Err(Error::InvalidRecipe(name.to_string()));
+            }
+        }
+        match &dep {
+            Dependency::Git { rev, branch, tag, .. } => {
+                crate::fetch::git::GitRef::from_fields(name, rev, branch, tag)?;
+            }
+            Dependency::Registry { version, .. } => {
+                VersionReq::from_str(version)?;
+            }
+            Dependency::Archive { sha256, .. } if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) => {
+                return Err(Error::InvalidChecksum(name.to_string()));
+            }
+            Dependency::Conan { conan, .. } if conan.split_once('/').is_none_or(|(n, v)| n.is_empty() || v.is_empty()) => {
+                return Err(Error::InvalidConanReference(name.to_string(), conan.clone()));
+            }
+            Dependency::PkgConfig {
+                version: Some(version), ..
+            } if crate::fetch::pkgconfig::requirement(version).is_none() => {
+                return Err(Error::InvalidPkgConfigVersion(name.to_string(), version.clone()));
+            }
+            _ => (),
+        }
+        Ok(dep)
+    }
+
+    /// makes the local paths of a dependency declared in 'base' independent of the working directory
+    pub fn rebase(&mut self, base: &Path) {
+        match self {
+            Self::Package { src, .. } if src != "vcpkg" => *src = base.join(&*src),
+            Self::Headers { headers, .. } => *headers = base.join(&*headers),
+            Self::Archive { archive, manifest, .. } => {
+                if !archive.contains("://") {
+                    *archive = base.join(&*archive).to_string_lossy().to_string();
+                }
+                if let Some(manifest) = manifest {
+                    *manifest = base.join(&*manifest);
+                }
+            }
+            _ => (),
+        }
+        if let Some(recipe) = self.recipe_mut() {
+            match recipe {
+                Recipe::Script(script) => *script = base.join(&*script),
+                Recipe::Table(table) => table.script = table.script.as_ref().map(|s| base.join(s)),
+            }
+        }
+    }
+
+    pub fn features(&self) -> &[String] {
+        match self {
+            Self::Git { features, .. }
+            | Self::Archive { features, .. }
+            | Self::Package { features, .. }
+            | Self::Headers { features, .. }
+            | Self::Registry { features, .. } => features,
+            Self::System { .. } | Self::PkgConfig { .. } | Self::Conan { .. } => &[],
+        }
+    }
+
+    fn recipe_mut(&mut self) -> Option<&mut Recipe> {
+        match self {
+            Self::Git { recipe, .. } | Self::Archive { recipe, .. } | Self::Package { recipe, .. } => recipe.as_mut(),
+            _ => None,
+        }
+    }
+
+    pub fn recipe(&self) -> Option<&Recipe> {
+        match self {
+            Self::Git { recipe, .. } | Self::Archive { recipe, .. } | Self::Package { recipe, .. } => recipe.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ConanOption {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+impl std::fmt::Display for ConanOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            // conan options are python values
+            Self::Bool(true) => write!(f, "True"),
+            Self::Bool(false) => write!(f, "False"),
+            Self::Int(i) => write!(f, "{i}"),
+            Self::Str(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+/// Replacements for dependencies anywhere in the graph, keyed by the source they replace (a git url, archive location
+/// or registry name) and then by dependency name, i.e. '[patch."https://github.com/x/y.git"] y = { path = "../y" }'.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Patches(BTreeMap<String, BTreeMap<String, Dependency>>);
+
+impl Patches {
+    /// 'https://host/x/y.git' and 'https://host/x/y/' name the same repository
+    fn normalize(source: &str) -> &str {
+        let source = source.trim_end_matches('/');
+        source.strip_suffix(".git").unwrap_or(source)
+    }
+
+    /// the replacement of a dependency, along with the (normalized) source it was declared for
+    pub fn get(&self, source: &str, name: &str) -> Option<(&str, &Dependency)> {
+        let (source, table) = self.0.get_key_value(Self::normalize(source))?;
+        Some((source.as_str(), table.get(name)?))
+    }
+
+    /// every patch as (source, name, replacement)
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str, &Dependency)> {
+        self.0
+            .iter()
+            .flat_map(|(source, table)| table.iter().map(move |(name, dep)| (source.as_str(), name.as_str(), dep)))
+    }
+
+    pub fn rebase(&mut self, base: &Path) {
+        self.0.values_mut().flat_map(BTreeMap::values_mut).for_each(|d| d.rebase(base));
+    }
+
+    /// patches in 'other' take precedence over those for the same source and name
+    pub fn merge(&mut self, other: Self) {
+        for (source, table) in other.0 {
+            self.0.entry(source).or_default().extend(table);
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Patches {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = BTreeMap::<String, toml::Table>::deserialize(deserializer)?;
+        let mut patches = BTreeMap::new();
+        for (source, table) in raw {
+            let mut deps = BTreeMap::new();
+            for (name, value) in table {
+                let dep = Dependency::from_value(&name, value).map_err(serde::de::Error::custom)?;
+                deps.insert(name, dep);
+            }
+            patches.insert(Self::normalize(&source).to_string(), deps);
+        }
+        Ok(Self(patches))
+    }
+}
+
+/// Builds a foreign (CMake, Make, Autotools or scripted) project, whose outputs are consumed as a generated '[staticlib]'.
+/// The string form is shorthand for '{ script = "..." }'.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Recipe {
+    Script(PathBuf),
+    Table(Box<RecipeTable>),
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RecipeTable {
+    pub cmake: Option<RecipeTool>,
+    pub make: Option<RecipeTool>,
+    pub configure: Option<RecipeTool>,
+    /// script (relative to the consuming project), run in the dependency root
+    pub script: Option<PathBuf>,
+    /// outputs, relative to the dependency root, default to 'include' and 'lib' in the install prefix
+    pub include: Option<PathBuf>,
+    pub libdir: Option<PathBuf>,
+    /// libraries to link, default to the dependency name
+    pub binaries: Vec<PathBuf>,
+    pub defines: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecipeTool {
+    /// extra arguments, e.g. '-DBUILD_SHARED_LIBS=OFF' for CMake or '--disable-shared' for configure
+    pub options: Vec<String>,
+    /// make targets, the default target if empty
+    pub targets: Vec<String>,
+}
+
+impl Recipe {
+    pub fn table(&self) -> RecipeTable {
+        match self {
+            Self::Script(script) => RecipeTable {
+                script: Some(script.clone()),
+                ..Default::default()
+            },
+            Self::Table(table) => (**table).clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VcpkgConfig {
+    #[serde(default = "VcpkgConfig::default_triplet")]
     pub triplet: String,
+    /// commit of the vcpkg registry that versions are resolved against, required to pin versions
+    pub baseline: Option<String>,
+    /// directories of custom or modified ports, relative to the project root
+    #[serde(default, rename = "overlay-ports")]
+    pub overlay_ports: Vec<PathBuf>,
+}
+
+impl Default for VcpkgConfig {
+    fn default() -> Self {
+        Self {
+            triplet: Self::default_triplet(),
+            baseline: None,
+            overlay_ports: Vec::new(),
+        }
+    }
+}
+
+impl VcpkgConfig {
+    /// the triplet vcpkg itself would pick for the host
+    fn default_triplet() -> String {
+        let arch = if cfg!(target_arch = "aarch64") {
+            "arm64"
+        } else if cfg!(target_arch = "x86") {
+            "x86"
+        } else {
+            "x64"
+        };
+        let os = if cfg!(windows) {
+            "windows"
+        } else if cfg!(target_os = "macos") {
+            "osx"
+        } else {
+            "linux"
+        };
+        format!("{arch}-{os}")
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -137,6 +456,65 @@ pub enum WarnLevel {
     High = 2,
 }
 
+/// which symbols of a shared library are exported, 'hidden' exports only those marked '<NAME>_API'
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Visibility {
+    Default,
+    Hidden,
+}
+
+/// how much debugging information is generated, 'debug-info = true|false' is 'full' or 'none'
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DebugInfo {
+    None,
+    LineTablesOnly,
+    Full,
+}
+
+/// what is removed from linked binaries (executables and shared libraries)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Strip {
+    None,
+    Debuginfo,
+    Symbols,
+}
+
+/// where the debug info of linked binaries goes, 'packed' moves it to one file next to the binary ('app.debug',
+/// 'app.dSYM'), 'unpacked' leaves it in a file per object ('.dwo', or the objects themselves on macos)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitDebuginfo {
+    Off,
+    Packed,
+    Unpacked,
+}
+
+/// the linker used by GNU toolchains ('-fuse-ld='), 'default' is whatever the compiler driver picks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Linker {
+    Default,
+    Lld,
+    Mold,
+    Gold,
+    Bfd,
+}
+
+impl std::fmt::Display for Linker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::Lld => write!(f, "lld"),
+            Self::Mold => write!(f, "mold"),
+            Self::Gold => write!(f, "gold"),
+            Self::Bfd => write!(f, "bfd"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Runtime {
@@ -150,8 +528,14 @@ pub enum Runtime {
 pub struct BuildProfile {
     pub baseprof: Profile,
 
+    /// everything the package itself is built with, including its private and public requirements
     pub defines: Vec<String>,
     pub include: Vec<PathBuf>,
+    /// only ever used by the package itself, never passed to consumers
+    pub private_defines: Vec<String>,
+    /// passed to all consumers, transitively
+    pub public_defines: Vec<String>,
+    pub public_include: Vec<PathBuf>,
     pub pch: Option<PathBuf>,
     pub settings: BuildSettings,
 
@@ -162,13 +546,14 @@ pub struct BuildProfile {
 impl BuildProfile {
     pub(super) fn debug(defaults: &SerdeBuildProfile) -> Self {
         let mut defines = vec!["VANGO_DEBUG".to_string()];
-        if let Some(def) = &defaults.defines {
-            defines.extend(def.iter().map(String::to_owned));
-        }
+        defines.extend(defaults.own_defines());
         Self {
             baseprof: Profile::Debug,
             defines,
-            include: defaults.include.iter().flatten().map(PathBuf::to_owned).collect(),
+            include: defaults.own_include(),
+            private_defines: defaults.private_defines.clone().unwrap_or_default(),
+            public_defines: defaults.public_defines.clone().unwrap_or_default(),
+            public_include: defaults.public_include.clone().unwrap_or_default(),
             pch: defaults.pch.clone(),
 
             settings: BuildSettings {
@@ -179,11 +564,18 @@ impl BuildProfile {
                 iso_compliant: defaults.build_settings.iso_compliant.unwrap_or(false),
                 warn_level: defaults.build_settings.warn_level.unwrap_or(WarnLevel::Basic),
                 warn_as_error: defaults.build_settings.warn_as_error.unwrap_or(false),
-                debug_info: defaults.build_settings.debug_info.unwrap_or(true),
+                debug_info: defaults.build_settings.debug_info.map_or(DebugInfo::Full, DebugInfo::from),
+                strip: defaults.build_settings.strip.unwrap_or(Strip::None),
+                split_debuginfo: defaults.build_settings.split_debuginfo.unwrap_or(SplitDebuginfo::Off),
                 runtime: defaults.build_settings.runtime.unwrap_or(Runtime::DynamicDebug),
                 aslr: defaults.build_settings.aslr.unwrap_or(true),
                 no_rtti: defaults.build_settings.no_rtti.unwrap_or(false),
                 no_except: defaults.build_settings.no_except.unwrap_or(false),
+                visibility: defaults.build_settings.visibility.unwrap_or(Visibility::Default),
+                linker: defaults.build_settings.linker.unwrap_or(Linker::Default),
+                thin_archive: defaults.build_settings.thin_archive.unwrap_or(false),
+                dead_code_elimination: defaults.build_settings.dead_code_elimination.unwrap_or(false),
+                icf: defaults.build_settings.icf.unwrap_or(false),
 
                 pthreads: defaults.build_settings.pthreads.unwrap_or(false),
                 asan: defaults.build_settings.sanitize.address.unwrap_or(false),
@@ -199,13 +591,14 @@ impl BuildProfile {
 
     pub(super) fn release(defaults: &SerdeBuildProfile) -> Self {
         let mut defines = vec!["VANGO_RELEASE".to_string()];
-        if let Some(def) = &defaults.defines {
-            defines.extend(def.iter().map(String::to_owned));
-        }
+        defines.extend(defaults.own_defines());
         Self {
             baseprof: Profile::Release,
             defines,
-            include: defaults.include.iter().flatten().map(PathBuf::to_owned).collect(),
+            include: defaults.own_include(),
+            private_defines: defaults.private_defines.clone().unwrap_or_default(),
+            public_defines: defaults.public_defines.clone().unwrap_or_default(),
+            public_include: defaults.public_include.clone().unwrap_or_default(),
             pch: defaults.pch.clone(),
 
             settings: BuildSettings {
@@ -216,11 +609,18 @@ impl BuildProfile {
                 iso_compliant: defaults.build_settings.iso_compliant.unwrap_or(false),
                 warn_level: defaults.build_settings.warn_level.unwrap_or(WarnLevel::Basic),
                 warn_as_error: defaults.build_settings.warn_as_error.unwrap_or(false),
-                debug_info: defaults.build_settings.debug_info.unwrap_or(false),
+                debug_info: defaults.build_settings.debug_info.map_or(DebugInfo::None, DebugInfo::from),
+                strip: defaults.build_settings.strip.unwrap_or(Strip::None),
+                split_debuginfo: defaults.build_settings.split_debuginfo.unwrap_or(SplitDebuginfo::Off),
                 runtime: defaults.build_settings.runtime.unwrap_or(Runtime::DynamicRelease),
                 aslr: defaults.build_settings.aslr.unwrap_or(true),
                 no_rtti: defaults.build_settings.no_rtti.unwrap_or(false),
                 no_except: defaults.build_settings.no_except.unwrap_or(false),
+                visibility: defaults.build_settings.visibility.unwrap_or(Visibility::Default),
+                linker: defaults.build_settings.linker.unwrap_or(Linker::Default),
+                thin_archive: defaults.build_settings.thin_archive.unwrap_or(false),
+                dead_code_elimination: defaults.build_settings.dead_code_elimination.unwrap_or(false),
+                icf: defaults.build_settings.icf.unwrap_or(false),
 
                 pthreads: defaults.build_settings.pthreads.unwrap_or(false),
                 asan: defaults.build_settings.sanitize.address.unwrap_or(false),
@@ -235,8 +635,11 @@ impl BuildProfile {
     }
 
     fn merge(mut self, other: SerdeBuildProfile) -> Self {
-        self.defines.extend(other.defines.unwrap_or_default());
-        self.include.extend(other.include.unwrap_or_default());
+        self.defines.extend(other.own_defines());
+        self.include.extend(other.own_include());
+        self.private_defines.extend(other.private_defines.unwrap_or_default());
+        self.public_defines.extend(other.public_defines.unwrap_or_default());
+        self.public_include.extend(other.public_include.unwrap_or_default());
         if let Some(pch) = other.pch {
             self.pch = Some(pch);
         }
@@ -248,11 +651,21 @@ impl BuildProfile {
         other.build_settings.iso_compliant.inspect(|s| self.settings.iso_compliant = *s);
         other.build_settings.warn_level.inspect(|s| self.settings.warn_level = *s);
         other.build_settings.warn_as_error.inspect(|s| self.settings.warn_as_error = *s);
-        other.build_settings.debug_info.inspect(|s| self.settings.debug_info = *s);
+        other.build_settings.debug_info.inspect(|s| self.settings.debug_info = (*s).into());
+        other.build_settings.strip.inspect(|s| self.settings.strip = *s);
+        other.build_settings.split_debuginfo.inspect(|s| self.settings.split_debuginfo = *s);
         other.build_settings.runtime.inspect(|s| self.settings.runtime = *s);
         other.build_settings.aslr.inspect(|s| self.settings.aslr = *s);
         other.build_settings.no_rtti.inspect(|s| self.settings.no_rtti = *s);
         other.build_settings.no_except.inspect(|s| self.settings.no_except = *s);
+        other.build_settings.visibility.inspect(|s| self.settings.visibility = *s);
+        other.build_settings.linker.inspect(|s| self.settings.linker = *s);
+        other.build_settings.thin_archive.inspect(|s| self.settings.thin_archive = *s);
+        other
+            .build_settings
+            .dead_code_elimination
+            .inspect(|s| self.settings.dead_code_elimination = *s);
+        other.build_settings.icf.inspect(|s| self.settings.icf = *s);
 
         other.build_settings.pthreads.inspect(|s| self.settings.pthreads = *s);
         other.build_settings.sanitize.address.inspect(|s| self.settings.asan = *s);
@@ -281,11 +694,20 @@ pub struct BuildSettings {
     pub iso_compliant: bool,
     pub warn_level: WarnLevel,
     pub warn_as_error: bool,
-    pub debug_info: bool,
+    pub debug_info: DebugInfo,
+    pub strip: Strip,
+    pub split_debuginfo: SplitDebuginfo,
     pub runtime: Runtime,
     pub aslr: bool,
     pub no_rtti: bool,
     pub no_except: bool,
+    pub visibility: Visibility,
+    pub linker: Linker,
+    /// static libraries only reference their objects (GNU toolchains)
+    pub thin_archive: bool,
+    pub dead_code_elimination: bool,
+    /// identical code folding
+    pub icf: bool,
 
     pub pthreads: bool,
     pub asan: bool,
@@ -299,6 +721,10 @@ struct SerdeBuildFile {
     package: SerdeBuild,
     vcpkg: Option<VcpkgConfig>,
     dependencies: toml::Table,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: toml::Table,
+    #[serde(default)]
+    patch: Patches,
     #[serde(default)]
     profile: HashMap<String, SerdeBuildProfile>,
 }
@@ -313,6 +739,8 @@ struct SerdeBuild {
     implib: Option<bool>,
     interface: Option<String>,
     runtime: Option<String>,
+    #[serde(rename = "copy-shared")]
+    copy_shared: Option<bool>,
 
     #[serde(flatten)]
     defaults: SerdeBuildProfile,
@@ -326,6 +754,10 @@ pub(super) struct SerdeBuildProfile {
 
     defines: Option<Vec<String>>,
     include: Option<Vec<PathBuf>>,
+    private_defines: Option<Vec<String>>,
+    private_include: Option<Vec<PathBuf>>,
+    public_defines: Option<Vec<String>>,
+    public_include: Option<Vec<PathBuf>>,
     pch: Option<PathBuf>,
 
     #[serde(flatten)]
@@ -335,6 +767,25 @@ pub(super) struct SerdeBuildProfile {
     linker_options: Option<Vec<String>>,
 }
 
+impl SerdeBuildProfile {
+    fn own_defines(&self) -> impl Iterator<Item = String> {
+        [&self.defines, &self.private_defines, &self.public_defines]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .cloned()
+    }
+
+    fn own_include(&self) -> Vec<PathBuf> {
+        [&self.include, &self.private_include, &self.public_include]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 struct SerdeBuildSettings {
@@ -345,18 +796,42 @@ struct SerdeBuildSettings {
     iso_compliant: Option<bool>,
     warn_level: Option<WarnLevel>,
     warn_as_error: Option<bool>,
-    debug_info: Option<bool>,
+    debug_info: Option<SerdeDebugInfo>,
+    strip: Option<Strip>,
+    split_debuginfo: Option<SplitDebuginfo>,
     runtime: Option<Runtime>,
 
     aslr: Option<bool>,
     no_rtti: Option<bool>,
     no_except: Option<bool>,
+    visibility: Option<Visibility>,
+    linker: Option<Linker>,
+    thin_archive: Option<bool>,
+    dead_code_elimination: Option<bool>,
+    icf: Option<bool>,
 
     pthreads: Option<bool>,
     #[serde(default)]
     sanitize: SerdeSanitizer,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum SerdeDebugInfo {
+    Enabled(bool),
+    Level(DebugInfo),
+}
+
+impl From<SerdeDebugInfo> for DebugInfo {
+    fn from(value: SerdeDebugInfo) -> Self {
+        match value {
+            SerdeDebugInfo::Enabled(true) => DebugInfo::Full,
+            SerdeDebugInfo::Enabled(false) => DebugInfo::None,
+            SerdeDebugInfo::Level(level) => level,
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 struct SerdeSanitizer {
@@ -2,7 +2,7 @@ use crate::{Error, log_error_ln, log_warn_ln};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, str::FromStr};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
@@ -44,6 +44,110 @@ impl FromStr for Version {
     }
 }
 
+/// A semver requirement such as '10.2', '^1.2.3', '~0.4', '=2.0.1', '>=1.0, <1.8' or '*'.
+/// A bare version is treated like cargo does, as a caret requirement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReqOp {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+}
+
+/// a (possibly partial) version with an operator, missing parts act as wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: ReqOp,
+    parts: Vec<u32>,
+}
+
+impl VersionReq {
+    pub fn matches(&self, v: &Version) -> bool {
+        self.comparators.iter().all(|c| c.matches(v))
+    }
+}
+
+impl Comparator {
+    fn matches(&self, v: &Version) -> bool {
+        let full = [v.major, v.minor, v.patch];
+        let prefix = &full[..self.parts.len()];
+        let lower = full.as_slice() >= self.parts.as_slice();
+        match self.op {
+            ReqOp::Exact => prefix == self.parts,
+            ReqOp::Greater => prefix > self.parts.as_slice(),
+            ReqOp::GreaterEq => prefix >= self.parts.as_slice(),
+            ReqOp::Less => prefix < self.parts.as_slice(),
+            ReqOp::LessEq => prefix <= self.parts.as_slice(),
+            ReqOp::Tilde => {
+                let fixed = self.parts.len().min(2);
+                full[..fixed] == self.parts[..fixed] && lower
+            }
+            ReqOp::Caret => {
+                // everything up to and including the first non-zero part is fixed
+                let fixed = self.parts.iter().position(|p| *p != 0).unwrap_or(self.parts.len() - 1) + 1;
+                full[..fixed] == self.parts[..fixed] && lower
+            }
+        }
+    }
+}
+
+impl FromStr for Comparator {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Error::MimicTomlSemverReq(s.to_string());
+        let (op, rest) = if let Some(rest) = s.strip_prefix(">=") {
+            (ReqOp::GreaterEq, rest)
+        } else if let Some(rest) = s.strip_prefix("<=") {
+            (ReqOp::LessEq, rest)
+        } else if let Some(rest) = s.strip_prefix('>') {
+            (ReqOp::Greater, rest)
+        } else if let Some(rest) = s.strip_prefix('<') {
+            (ReqOp::Less, rest)
+        } else if let Some(rest) = s.strip_prefix('=') {
+            (ReqOp::Exact, rest)
+        } else if let Some(rest) = s.strip_prefix('~') {
+            (ReqOp::Tilde, rest)
+        } else if let Some(rest) = s.strip_prefix('^') {
+            (ReqOp::Caret, rest)
+        } else if s.contains('*') {
+            (ReqOp::Exact, s)
+        } else {
+            (ReqOp::Caret, s)
+        };
+
+        let mut parts = Vec::new();
+        for part in rest.trim().split('.') {
+            if part == "*" || part == "x" {
+                break;
+            }
+            parts.push(part.parse().map_err(|_| err())?);
+        }
+        if parts.len() > 3 || (parts.is_empty() && op != ReqOp::Exact) {
+            return Err(err());
+        }
+        Ok(Comparator { op, parts })
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let comparators = s
+            .split(',')
+            .map(|c| Comparator::from_str(c.trim()).map_err(|_| Error::MimicTomlSemverReq(s.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(VersionReq { comparators })
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum ProjKind {
     #[default]
@@ -79,7 +183,7 @@ impl FromStr for ProjKind {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ToolChain {
     Msvc,
     Gcc,
@@ -171,7 +275,6 @@ impl ToolChain {
     pub fn is_msvc(self) -> bool {
         matches!(self, Self::Msvc | Self::ClangMsvc)
     }
-    #[allow(dead_code)]
     pub fn is_gnu(self) -> bool {
         matches!(self, Self::Gcc | Self::ClangGnu | Self::Zig | Self::Emcc)
     }
@@ -188,6 +291,37 @@ impl ToolChain {
         matches!(self, Self::Emcc)
     }
 
+    /// MSVC links with LINK.exe or lld-link, Zig and Emscripten with their own lld, mold and gold only link ELF
+    pub fn supports_linker(self, linker: super::Linker) -> bool {
+        use super::Linker;
+        match (self, linker) {
+            (_, Linker::Default) => true,
+            (Self::ClangMsvc | Self::Zig, Linker::Lld) => true,
+            (Self::Gcc | Self::ClangGnu, Linker::Lld) => true,
+            (Self::Gcc | Self::ClangGnu, Linker::Mold | Linker::Gold) => cfg!(target_os = "linux"),
+            (Self::Gcc | Self::ClangGnu, Linker::Bfd) => !cfg!(target_os = "macos"),
+            _ => false,
+        }
+    }
+    /// identical code folding is done by LINK.exe and lld-link, and by lld, mold and gold
+    pub fn supports_icf(self, linker: super::Linker) -> bool {
+        use super::Linker;
+        match self {
+            Self::Msvc | Self::ClangMsvc | Self::Zig => true,
+            Self::Gcc | Self::ClangGnu => matches!(linker, Linker::Lld | Linker::Mold | Linker::Gold),
+            Self::Emcc => false,
+        }
+    }
+
+    /// thin archives are made by GNU ar and llvm-ar, 'ar' of gcc on macos and the BSDs reads 'T' as "truncate names"
+    pub fn supports_thin_archive(self) -> bool {
+        match self {
+            Self::Gcc => cfg!(any(target_os = "linux", windows)),
+            Self::ClangGnu | Self::Zig | Self::Emcc => true,
+            Self::Msvc | Self::ClangMsvc => false,
+        }
+    }
+
     pub fn shared_lib_prefix() -> &'static str {
         if cfg!(windows) { "" } else { "lib" }
     }
@@ -278,6 +412,18 @@ impl ToolChain {
             }
         }
     }
+    /// moves debug info out of linked binaries and strips them (GNU toolchains only, see 'SplitDebuginfo')
+    pub fn objcopy(self) -> std::process::Command {
+        match self {
+            Self::Gcc => std::process::Command::new("objcopy"),
+            Self::Zig => {
+                let mut cmd = std::process::Command::new("zig");
+                cmd.arg("objcopy");
+                cmd
+            }
+            _ => std::process::Command::new("llvm-objcopy"),
+        }
+    }
 }
 
 impl Display for ToolChain {
@@ -430,6 +576,34 @@ impl FromStr for Lang {
 mod tests {
     use super::*;
 
+    #[test]
+    pub fn version_req_matches() {
+        let v = |s: &str| Version::from_str(s).unwrap();
+        let req = |s: &str| VersionReq::from_str(s).unwrap();
+        assert!(req("10.2").matches(&v("10.2.0")));
+        assert!(req("10.2").matches(&v("10.9.1")));
+        assert!(!req("10.2").matches(&v("10.1.9")));
+        assert!(!req("10.2").matches(&v("11.0.0")));
+        assert!(req("^0.2.3").matches(&v("0.2.9")));
+        assert!(!req("^0.2.3").matches(&v("0.3.0")));
+        assert!(req("^0.0.3").matches(&v("0.0.3")));
+        assert!(!req("^0.0.3").matches(&v("0.0.4")));
+        assert!(req("~1.2.3").matches(&v("1.2.7")));
+        assert!(!req("~1.2.3").matches(&v("1.3.0")));
+        assert!(req("~1").matches(&v("1.9.0")));
+        assert!(req("=2.0").matches(&v("2.0.5")));
+        assert!(!req("=2.0.1").matches(&v("2.0.5")));
+        assert!(req(">=1.0, <1.8").matches(&v("1.7.99")));
+        assert!(!req(">=1.0, <1.8").matches(&v("1.8.0")));
+        assert!(!req(">1.2").matches(&v("1.2.9")));
+        assert!(req("<=1.2").matches(&v("1.2.9")));
+        assert!(req("*").matches(&v("0.0.1")));
+        assert!(req("1.*").matches(&v("1.4.0")));
+        assert!(VersionReq::from_str("1.2.3.4").is_err());
+        assert!(VersionReq::from_str("abc").is_err());
+        assert!(VersionReq::from_str("^").is_err());
+    }
+
     #[test]
     pub fn parse_lang_cpp() {
         assert_eq!(Lang::from_str("c++98").unwrap(), Lang::Cpp(98));
@@ -476,4 +650,27 @@ mod tests {
         assert!(Lang::from_str("C++03").unwrap() >= Lang::from_str("C++98").unwrap());
         assert!(Lang::from_str("C++11").unwrap() >= Lang::from_str("C++98").unwrap());
     }
+
+    #[test]
+    pub fn linker_support() {
+        use crate::config::Linker;
+        assert!(ToolChain::Msvc.supports_linker(Linker::Default));
+        assert!(!ToolChain::Msvc.supports_linker(Linker::Lld));
+        assert!(ToolChain::ClangMsvc.supports_linker(Linker::Lld));
+        assert!(!ToolChain::Zig.supports_linker(Linker::Mold));
+        assert!(!ToolChain::Emcc.supports_linker(Linker::Lld));
+        assert!(ToolChain::Gcc.supports_linker(Linker::Lld));
+        assert_eq!(ToolChain::Gcc.supports_linker(Linker::Mold), cfg!(target_os = "linux"));
+        assert_eq!(ToolChain::ClangGnu.supports_linker(Linker::Bfd), !cfg!(target_os = "macos"));
+
+        assert!(ToolChain::Msvc.supports_icf(Linker::Default));
+        assert!(!ToolChain::Gcc.supports_icf(Linker::Default));
+        assert!(!ToolChain::Gcc.supports_icf(Linker::Bfd));
+        assert!(ToolChain::ClangGnu.supports_icf(Linker::Mold));
+        assert!(!ToolChain::Emcc.supports_icf(Linker::Default));
+
+        assert!(!ToolChain::Msvc.supports_thin_archive());
+        assert!(ToolChain::ClangGnu.supports_thin_archive());
+        assert_eq!(ToolChain::Gcc.supports_thin_archive(), !cfg!(target_os = "macos"));
+    }
 }
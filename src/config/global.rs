@@ -0,0 +1,187 @@
+use super::Patches;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+pub const CONFIG_FILE: &str = ".vango/config.toml";
+
+/// Machine or project wide settings, as opposed to the per-package manifest. Read from
+/// '~/.vango/config.toml', then overridden by '.vango/config.toml' in the root project.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GlobalConfig {
+    pub source: SourceConfig,
+    pub net: NetConfig,
+    pub registry: RegistryDefaults,
+    pub registries: BTreeMap<String, RegistryConfig>,
+    /// local overrides of dependencies, e.g. to develop a library and its consumers side by side
+    pub patch: Patches,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct SourceConfig {
+    /// directory (relative to the config location) that replaces the package cache and network
+    pub vendor: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NetConfig {
+    pub offline: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RegistryDefaults {
+    /// registry used by dependencies that do not name one, optional if only one registry is configured
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RegistryConfig {
+    /// local directory (relative to the config location) or git url of the registry
+    pub index: String,
+}
+
+impl RegistryConfig {
+    pub fn is_git(&self) -> bool {
+        self.index.contains("://") || self.index.starts_with("git@") || self.index.ends_with(".git")
+    }
+}
+
+impl GlobalConfig {
+    pub fn load(root: &Path) -> Result<Self, Error> {
+        let mut config = Self::default();
+        if let Some(home) = std::env::home_dir() {
+            config.merge(Self::read(&home)?);
+        }
+        config.merge(Self::read(root)?);
+        Ok(config)
+    }
+
+    fn read(dir: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(dir.join(CONFIG_FILE)) {
+            Ok(file) => {
+                let mut config: Self = toml::from_str(&file)?;
+                config.source.vendor = config.source.vendor.map(|v| dir.join(v));
+                config.patch.rebase(dir);
+                for registry in config.registries.values_mut().filter(|r| !r.is_git()) {
+                    // the index location is part of lockfile sources, so keep it free of '..'
+                    let index = dir.join(&registry.index);
+                    registry.index = std::fs::canonicalize(&index).unwrap_or(index).to_string_lossy().to_string();
+                }
+                Ok(config)
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::FileSystem(e)),
+        }
+    }
+
+    fn merge(&mut self, other: Self) {
+        if other.source.vendor.is_some() {
+            self.source.vendor = other.source.vendor;
+        }
+        self.net.offline |= other.net.offline;
+        if other.registry.default.is_some() {
+            self.registry.default = other.registry.default;
+        }
+        self.registries.extend(other.registries);
+        self.patch.merge(other.patch);
+    }
+
+    /// looks up a registry by name, or the default registry
+    pub fn registry(&self, name: Option<&str>) -> Result<(&str, &RegistryConfig), Error> {
+        let name = match name.or(self.registry.default.as_deref()) {
+            Some(name) => name,
+            None if self.registries.len() == 1 => self.registries.keys().next().unwrap(),
+            None => return Err(Error::NoDefaultRegistry),
+        };
+        self.registries
+            .get_key_value(name)
+            .map(|(k, v)| (k.as_str(), v))
+            .ok_or(Error::UnknownRegistry(name.to_string()))
+    }
+}
+
+/// sets a single key in the project config, preserving all other settings
+pub fn write_project_key(root: &Path, table: &str, key: &str, value: toml_edit::Value) -> Result<(), Error> {
+    let path = root.join(CONFIG_FILE);
+    let mut doc: toml_edit::DocumentMut = match std::fs::read_to_string(&path) {
+        Ok(file) => file.parse()?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml_edit::DocumentMut::new(),
+        Err(e) => return Err(Error::FileSystem(e)),
+    };
+    doc.entry(table)
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or(Error::InvalidConfig(table.to_string()))?
+        .insert(key, toml_edit::Item::Value(value));
+    std::fs::create_dir_all(path.parent().unwrap())?;
+    std::fs::write(&path, doc.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+[source]
+vendor = "vendor"
+
+[net]
+offline = true
+
+[registries.company]
+index = "https://example.com/vango-index.git"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.source.vendor, Some(PathBuf::from("vendor")));
+        assert!(config.net.offline);
+        assert!(config.registries["company"].is_git());
+        assert_eq!(config.registry(None).unwrap().0, "company");
+        assert!(config.registry(Some("other")).is_err());
+        assert_eq!(toml::from_str::<GlobalConfig>("").unwrap(), GlobalConfig::default());
+    }
+
+    #[test]
+    fn merge_overrides() {
+        let mut base = GlobalConfig {
+            source: SourceConfig {
+                vendor: Some("/home/vendor".into()),
+            },
+            net: NetConfig { offline: true },
+            ..Default::default()
+        };
+        base.merge(GlobalConfig::default());
+        assert_eq!(base.source.vendor, Some(PathBuf::from("/home/vendor")));
+        assert!(base.net.offline);
+        base.merge(GlobalConfig {
+            source: SourceConfig {
+                vendor: Some("/project/vendor".into()),
+            },
+            ..Default::default()
+        });
+        assert_eq!(base.source.vendor, Some(PathBuf::from("/project/vendor")));
+    }
+
+    #[test]
+    fn write_key_keeps_comments() {
+        let root = std::env::temp_dir().join(format!("vango-test-config-{}", std::process::id()));
+        std::fs::create_dir_all(root.join(".vango")).unwrap();
+        std::fs::write(root.join(CONFIG_FILE), "# mirrors\n[net]\noffline = true # ci\n").unwrap();
+        write_project_key(&root, "source", "vendor", "vendor".into()).unwrap();
+        let file = std::fs::read_to_string(root.join(CONFIG_FILE)).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert_eq!(file, "# mirrors\n[net]\noffline = true # ci\n\n[source]\nvendor = \"vendor\"\n");
+    }
+}
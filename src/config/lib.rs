@@ -81,7 +81,7 @@ impl LibFile {
                     include: "include".into(),
                     libdir: libbase.join(&k),
                     binaries: if haslib { vec![name.clone().into()] } else { Vec::new() },
-                    defines: p.defines,
+                    defines: p.defines.into_iter().filter(|d| !p.private_defines.contains(d)).collect(),
                 };
                 (k, prof)
             })
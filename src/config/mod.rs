@@ -1,10 +1,12 @@
 mod build;
 mod elems;
+mod global;
 mod lib;
 
 use crate::error::Error;
 pub use build::*;
 pub use elems::*;
+pub use global::*;
 pub use lib::*;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -47,7 +49,7 @@ impl VangoFile {
 mod tests {
 
     use super::{Lang, ProjKind, VangoFile, build::*, lib::*};
-    use std::{collections::HashMap, str::FromStr};
+    use std::{collections::HashMap, path::Path, str::FromStr};
 
     #[test]
     fn parse_buildfile() {
@@ -68,27 +70,42 @@ SFML    = { git="https://github.com/SFML/SFML.git",     recipe="recipes/SFML.bat
 LuaJIT  = { git="https://github.com/LuaJIT/LuaJIT.git", recipe="recipes/LuaJIT.bat" }
 "#;
 
-        let mut dependencies = Vec::new();
-        dependencies.push((String::new(), Dependency::Package {
-            src: "../engine".into(),
-            targets: vec![],
-            features: vec![],
-        }));
-        dependencies.push((String::new(), Dependency::Package {
-            src: "../../NavMesh".into(),
-            targets: vec![],
-            features: vec![],
-        }));
-        dependencies.push((String::new(), Dependency::Git {
-            git: "https://github.com/SFML/SFML.git".into(),
-            tag: None,
-            features: vec!["graphics".into()],
-        }));
-        dependencies.push((String::new(), Dependency::Git {
-            git: "https://github.com/LuaJIT/LuaJIT.git".into(),
-            tag: None,
-            features: vec![],
-        }));
+        let dependencies = vec![
+            ("engine".to_string(), Dependency::Package {
+                src: "../engine".into(),
+                targets: vec![],
+                recipe: None,
+                features: vec![],
+                version: None,
+            }),
+            ("NavMesh".to_string(), Dependency::Package {
+                src: "../../NavMesh".into(),
+                targets: vec![],
+                recipe: None,
+                features: vec![],
+                version: None,
+            }),
+            ("SFML".to_string(), Dependency::Git {
+                git: "https://github.com/SFML/SFML.git".into(),
+                tag: None,
+                branch: None,
+                rev: None,
+                path: None,
+                headers: None,
+                recipe: Some(Recipe::Script("recipes/SFML.bat".into())),
+                features: vec!["graphics".into()],
+            }),
+            ("LuaJIT".to_string(), Dependency::Git {
+                git: "https://github.com/LuaJIT/LuaJIT.git".into(),
+                tag: None,
+                branch: None,
+                rev: None,
+                path: None,
+                headers: None,
+                recipe: Some(Recipe::Script("recipes/LuaJIT.bat".into())),
+                features: vec![],
+            }),
+        ];
 
         let mut profiles: HashMap<String, BuildProfile> = HashMap::new();
         profiles.insert(
@@ -118,13 +135,308 @@ LuaJIT  = { git="https://github.com/LuaJIT/LuaJIT.git", recipe="recipes/LuaJIT.b
                 toolchain: None,
                 interface: Lang::Cpp(120),
                 runtime: None,
-                vcpkg: VcpkgConfig{ triplet: "x64-linux".to_string() },
+                copy_shared: false,
+                vcpkg: VcpkgConfig::default(),
                 dependencies,
+                dev_dependencies: vec![],
+                patch: Patches::default(),
                 profiles,
             })
         );
     }
 
+    #[test]
+    fn parse_git_dependencies() {
+        let file = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+utils = { git="https://example.com/mono.git", rev="4f2a9c1", path="libs/utils" }
+json  = { git="https://example.com/json.git", branch="develop", headers="include" }
+local = { path="../local" }
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        assert_eq!(build.dependencies[0], ("utils".to_string(), Dependency::Git {
+            git: "https://example.com/mono.git".into(),
+            tag: None,
+            branch: None,
+            rev: Some("4f2a9c1".into()),
+            path: Some("libs/utils".into()),
+            headers: None,
+            recipe: None,
+            features: vec![],
+        }));
+        assert_eq!(build.dependencies[1], ("json".to_string(), Dependency::Git {
+            git: "https://example.com/json.git".into(),
+            tag: None,
+            branch: Some("develop".into()),
+            rev: None,
+            path: None,
+            headers: Some("include".into()),
+            recipe: None,
+            features: vec![],
+        }));
+        assert_eq!(build.dependencies[2], ("local".to_string(), Dependency::Package {
+            src: "../local".into(),
+            targets: vec![],
+            recipe: None,
+            features: vec![],
+            version: None,
+        }));
+
+        let conflict = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+utils = { git="https://example.com/utils.git", tag="v1.0.0", branch="main" }
+"#;
+        assert!(VangoFile::from_str(conflict).is_err());
+    }
+
+    #[test]
+    fn parse_registry_dependencies() {
+        let file = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+fmt = "10.2"
+spdlog = { version="~1.14", registry="company" }
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        assert_eq!(build.dependencies[0], ("fmt".to_string(), Dependency::Registry {
+            version: "10.2".into(),
+            registry: None,
+            features: vec![],
+        }));
+        assert_eq!(build.dependencies[1], ("spdlog".to_string(), Dependency::Registry {
+            version: "~1.14".into(),
+            registry: Some("company".into()),
+            features: vec![],
+        }));
+
+        let invalid = file.replace("\"10.2\"", "\"ten\"");
+        assert!(VangoFile::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_archive_dependencies() {
+        let sha = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        let file = format!(
+            r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+sdk = {{ archive="https://example.com/sdk-1.2.tar.gz", sha256="{sha}", strip-prefix=1, manifest="recipes/sdk.toml" }}
+"#
+        );
+
+        let build = VangoFile::from_str(&file).unwrap().unwrap_build();
+        assert_eq!(build.dependencies[0], ("sdk".to_string(), Dependency::Archive {
+            archive: "https://example.com/sdk-1.2.tar.gz".into(),
+            sha256: sha.into(),
+            strip_prefix: 1,
+            manifest: Some("recipes/sdk.toml".into()),
+            headers: None,
+            recipe: None,
+            features: vec![],
+        }));
+
+        let invalid = file.replace(sha, "abc");
+        assert!(VangoFile::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_recipes() {
+        let file = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+zlib = { git="https://github.com/madler/zlib.git", tag="v1.3.1", recipe={ cmake={ options=["-DZLIB_BUILD_EXAMPLES=OFF"] }, binaries=["z"] } }
+lua  = { path="../lua", recipe={ make={ targets=["linux"] }, include="src", libdir="src" } }
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        assert_eq!(build.dependencies[0].1.recipe(), Some(&Recipe::Table(Box::new(RecipeTable {
+            cmake: Some(RecipeTool {
+                options: vec!["-DZLIB_BUILD_EXAMPLES=OFF".into()],
+                targets: vec![],
+            }),
+            binaries: vec!["z".into()],
+            ..Default::default()
+        }))));
+        assert_eq!(build.dependencies[1].1.recipe(), Some(&Recipe::Table(Box::new(RecipeTable {
+            make: Some(RecipeTool {
+                options: vec![],
+                targets: vec!["linux".into()],
+            }),
+            include: Some("src".into()),
+            libdir: Some("src".into()),
+            ..Default::default()
+        }))));
+
+        let ambiguous = file.replace("make={", "configure={}, make={");
+        assert!(VangoFile::from_str(&ambiguous).is_err());
+        let empty = file.replace("make={ targets=[\"linux\"] }, ", "");
+        assert!(VangoFile::from_str(&empty).is_err());
+    }
+
+    #[test]
+    fn parse_dev_dependencies() {
+        let file = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+fmt = "10.2"
+
+[dev-dependencies]
+mocks = { path="../mocks" }
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        assert_eq!(build.dependencies.len(), 1);
+        assert_eq!(build.dev_dependencies, vec![("mocks".to_string(), Dependency::Package {
+            src: "../mocks".into(),
+            targets: vec![],
+            recipe: None,
+            features: vec![],
+            version: None,
+        })]);
+
+        let invalid = file.replace("{ path=\"../mocks\" }", "\"ten\"");
+        assert!(VangoFile::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_patches() {
+        let file = r#"
+[package]
+name = "Shimmy"
+version = "0.1.0"
+lang = "C++20"
+
+[dependencies]
+y = { git="https://github.com/x/y.git" }
+
+[patch."https://github.com/x/y.git"]
+y = { path="../y" }
+
+[patch.company]
+fmt = "10.2.1"
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        let local = Dependency::Package {
+            src: "../y".into(),
+            targets: vec![],
+            recipe: None,
+            features: vec![],
+            version: None,
+        };
+        assert_eq!(build.patch.get("https://github.com/x/y", "y"), Some(("https://github.com/x/y", &local)));
+        assert_eq!(build.patch.get("https://github.com/x/y.git/", "y").map(|p| p.1), Some(&local));
+        assert!(build.patch.get("https://github.com/x/z.git", "y").is_none());
+        assert!(matches!(build.patch.get("company", "fmt"), Some((_, Dependency::Registry { .. }))));
+
+        let mut patches = build.patch.clone();
+        patches.rebase(Path::new("/work/app"));
+        let overrides: Patches = toml::from_str("[\"https://github.com/x/y\"]\ny = { path=\"/dev/y\" }").unwrap();
+        patches.merge(overrides);
+        assert!(matches!(patches.get("https://github.com/x/y", "y"), Some((_, Dependency::Package { src, .. })) if src == Path::new("/dev/y")));
+        assert!(matches!(patches.get("company", "fmt"), Some((_, Dependency::Registry { .. }))));
+        assert_eq!(patches.iter().count(), 2);
+
+        let invalid = file.replace("\"10.2.1\"", "\"ten\"");
+        assert!(VangoFile::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_visibility() {
+        let file = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+lang = "C++20"
+kind = "staticlib"
+defines = ["FOO_LEGACY"]
+private-defines = ["FOO_INTERNAL"]
+private-include = ["detail"]
+public-defines = ["FOO_STATIC"]
+public-include = ["generated"]
+
+[dependencies]
+
+[profile.release]
+public-defines = ["FOO_NDEBUG"]
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        let release = build.get(&crate::config::Profile::Release).unwrap();
+        assert_eq!(release.defines, ["VANGO_RELEASE", "FOO_LEGACY", "FOO_INTERNAL", "FOO_STATIC", "FOO_NDEBUG"]);
+        assert_eq!(release.include, [Path::new("detail"), Path::new("generated"), Path::new("src")]);
+        assert_eq!(release.private_defines, ["FOO_INTERNAL"]);
+        assert_eq!(release.public_defines, ["FOO_STATIC", "FOO_NDEBUG"]);
+        assert_eq!(release.public_include, [Path::new("generated")]);
+
+        let mut lib = LibFile::from_build(build, crate::config::ToolChain::Gcc).unwrap();
+        let exported = lib.take(&crate::config::Profile::Release).unwrap();
+        assert_eq!(exported.defines, ["VANGO_RELEASE", "FOO_LEGACY", "FOO_STATIC", "FOO_NDEBUG"]);
+    }
+
+    #[test]
+    fn parse_debuginfo_settings() {
+        use crate::config::{DebugInfo, Profile, SplitDebuginfo, Strip};
+        let file = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+lang = "C++20"
+debug-info = false
+
+[dependencies]
+
+[profile.release]
+debug-info = "line-tables-only"
+strip = "symbols"
+split-debuginfo = "packed"
+
+[profile.symbolicated]
+inherits = "release"
+debug-info = true
+"#;
+
+        let build = VangoFile::from_str(file).unwrap().unwrap_build();
+        let debug = build.get(&Profile::Debug).unwrap().settings;
+        assert_eq!(debug.debug_info, DebugInfo::None);
+        assert_eq!((debug.strip, debug.split_debuginfo), (Strip::None, SplitDebuginfo::Off));
+        let release = build.get(&Profile::Release).unwrap().settings;
+        assert_eq!(release.debug_info, DebugInfo::LineTablesOnly);
+        assert_eq!((release.strip, release.split_debuginfo), (Strip::Symbols, SplitDebuginfo::Packed));
+        let custom = build.get(&Profile::Custom("symbolicated".into())).unwrap().settings;
+        assert_eq!(custom.debug_info, DebugInfo::Full);
+
+        assert!(VangoFile::from_str(&file.replace("\"line-tables-only\"", "\"lines\"")).is_err());
+    }
+
     #[test]
     fn parse_libfile() {
         let file = r#"
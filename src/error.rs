@@ -8,6 +8,8 @@ pub enum Error {
     BadAction(String),
     // #[error("not enough arguments provided to '{0}' action")]
     // MissingArgs(String),
+    #[error("action '{0}' requires option '{1}'")]
+    MissingOption(String, String),
     #[error("unexpected arguments provided to '{0}' action: '{1:?}'")]
     ExtraArgs(String, Vec<String>),
     #[error("directory '{0}' does not contain a build manifest (Vango.toml)")]
@@ -16,6 +18,8 @@ pub enum Error {
     TomlParse(#[from] toml::de::Error),
     #[error("toml parse error: `{0}` is not a valid semver string")]
     MimicTomlSemver(String),
+    #[error("toml parse error: `{0}` is not a valid semver requirement")]
+    MimicTomlSemverReq(String),
     #[error("toml parse error: unknown variant `{0}`, expected one of `app`, `sharedlib`, `staticlib`\nin `package`\n")]
     MimicTomlProjkind(String),
     #[error("manifest in '{0}' does not contain header '[package]' or '[staticlib]'")]
@@ -43,12 +47,18 @@ pub enum Error {
     ArchiverNotFound(crate::config::ToolChain),
     #[error("toolchain '{0}' linker is unavailable")]
     LinkerNotFound(crate::config::ToolChain),
+    #[error("linker '{0}' is not supported by toolchain '{1}' on this platform")]
+    UnsupportedLinker(crate::config::Linker, crate::config::ToolChain),
     #[error("failed to compile project '{0}'")]
     CompilerFail(PathBuf),
     #[error("failed to archive project '{0}'")]
     ArchiverFail(PathBuf),
     #[error("failed to link project '{0}'")]
     LinkerFail(PathBuf),
+    #[error("'{0}' is unavailable, it is required to split debug info or strip binaries")]
+    StripToolNotFound(String),
+    #[error("failed to split debug info or strip '{0}': {1}")]
+    StripFail(PathBuf, String),
     #[error("project '{0}' does not contain 'src' directory")]
     MissingSource(String),
     #[error("project '{0}' does not contain library 'include' directory")]
@@ -61,6 +71,84 @@ pub enum Error {
     LibNotExe(String),
     #[error("process '{0}' was killed by the OS ({1})")]
     ExeKilled(PathBuf, String),
+    #[error("git is unavailable (make sure it is installed and in your PATH)")]
+    GitNotFound,
+    #[error("'git {0}' failed: {1}")]
+    GitFail(String, String),
+    #[error("git dependency '{0}' may only specify one of 'rev', 'branch' or 'tag'")]
+    ConflictingGitRefs(String),
+    #[error("lockfile 'Vango.lock' needs to be updated for dependency '{0}', but '--locked' was passed")]
+    LockOutdated(String),
+    #[error("package '{0}' is not present in 'Vango.lock'")]
+    PackageNotLocked(String),
+    #[error("package '{0}' is not available offline (not in the package cache)")]
+    OfflineFetch(String),
+    #[error("package '{0}' is missing from the vendor directory (run 'vango vendor')")]
+    NotVendored(String),
+    #[error("vendored package '{0}' does not match 'Vango.lock' (run 'vango vendor')")]
+    VendorOutdated(String),
+    #[error("config table '{0}' in '.vango/config.toml' is invalid")]
+    InvalidConfig(String),
+    #[error("registry '{0}' is not configured (see '[registries]' in '~/.vango/config.toml')")]
+    UnknownRegistry(String),
+    #[error("no default registry is configured (set 'default' under '[registry]' in '~/.vango/config.toml')")]
+    NoDefaultRegistry,
+    #[error("no version of package '{0}' matching '{1}' was found in registry '{2}'")]
+    NoMatchingVersion(String, String, String),
+    #[error("archive dependency '{0}' must specify 'sha256' as 64 hexadecimal digits")]
+    InvalidChecksum(String),
+    #[error("checksum mismatch for package '{0}': expected {1}, found {2}")]
+    ChecksumMismatch(String, String, String),
+    #[error("version {1} of package '{0}' is already published")]
+    AlreadyPublished(String, crate::config::Version),
+    #[error("dependency '{0}' is a local path, packages with local dependencies can not be published")]
+    PublishLocalDependency(String),
+    #[error("failed to download '{0}'")]
+    DownloadFail(String),
+    #[error("archive entry '{0}' links or extracts outside of the package directory")]
+    UnsafeArchiveEntry(PathBuf),
+    #[error("recipe of dependency '{0}' must specify exactly one of 'cmake', 'make', 'configure' or 'script'")]
+    InvalidRecipe(String),
+    #[error("'{0}' is unavailable (required by the recipe of '{1}')")]
+    RecipeToolNotFound(String, String),
+    #[error("recipe of dependency '{0}' failed at '{1}':\n{2}")]
+    RecipeFail(String, String, String),
+    #[error("dependency cycle detected: {0}")]
+    DependencyCycle(String),
+    #[error("conflicting versions of package '{0}' in the dependency graph: {1} ({2}) and {3} ({4})")]
+    VersionConflict(String, crate::config::Version, PathBuf, crate::config::Version, PathBuf),
+    #[error("dependency '{0}' must specify exactly one of '--path', '--headers', '--git', '--version' or '--system'")]
+    AmbiguousDependency(String),
+    #[error("dependency '{0}' is not declared in '[{1}]'")]
+    DependencyNotDeclared(String, String),
+    #[error("toml parse error: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+    #[error("vcpkg is unavailable (make sure it is installed and in your PATH, or set VCPKG_ROOT)")]
+    VcpkgNotFound,
+    #[error("'vcpkg install' failed:\n{0}")]
+    VcpkgFail(String),
+    #[error("pkg-config dependency '{0}' has an invalid version requirement '{1}' (expected i.e. '>=2.26')")]
+    InvalidPkgConfigVersion(String, String),
+    #[error("pkg-config is unavailable (make sure it is installed and in your PATH, or set PKG_CONFIG)")]
+    PkgConfigNotFound,
+    #[error("pkg-config could not resolve '{0}':\n{1}")]
+    PkgConfigFail(String, String),
+    #[error("conan dependency '{0}' has an invalid reference '{1}' (expected 'name/version')")]
+    InvalidConanReference(String, String),
+    #[error("conan is unavailable (make sure it is installed and in your PATH)")]
+    ConanNotFound,
+    #[error("'conan install' failed:\n{0}")]
+    ConanFail(String),
+    #[error("could not detect the version of the {0} compiler, which conan requires as 'compiler.version' (make sure it is in your PATH)")]
+    ConanCompilerVersion(crate::config::ToolChain),
+    #[error("unknown tree format '{0}' (expected 'text' or 'dot')")]
+    UnknownTreeFormat(String),
+    #[error("package '{0}' is not part of the dependency graph")]
+    NotInGraph(String),
+    #[error("project '{0}' is not a library, only libraries can be packaged")]
+    PackageNotLib(String),
+    #[error("project '{0}' is not installed in '{1}' (no install manifest was found)")]
+    NotInstalled(String, PathBuf),
     #[error("OS error: {0}")]
     FileSystem(#[from] std::io::Error),
 
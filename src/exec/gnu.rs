@@ -1,7 +1,7 @@
 use super::{BuildInfo, PreCompHead, output};
 use crate::{
     Error,
-    config::{ProjKind, Runtime, WarnLevel},
+    config::{BuildSettings, DebugInfo, Linker, ProjKind, Runtime, SplitDebuginfo, Strip, ToolChain, Visibility, WarnLevel},
     log_info_ln,
 };
 use std::path::{Path, PathBuf};
@@ -58,8 +58,28 @@ pub(super) fn compile(src: &Path, obj: &Path, info: &BuildInfo, pch: &PreCompHea
     if info.settings.opt_linktime {
         cmd.arg("-flto");
     }
-    if info.settings.debug_info {
-        cmd.arg("-g");
+    match info.settings.debug_info {
+        DebugInfo::None => (),
+        DebugInfo::LineTablesOnly if info.toolchain.is_clang() => {
+            cmd.arg("-gline-tables-only");
+        }
+        DebugInfo::LineTablesOnly => {
+            cmd.arg("-g1");
+        }
+        DebugInfo::Full => {
+            cmd.arg("-g");
+        }
+    }
+    if info.settings.debug_info != DebugInfo::None
+        && info.settings.split_debuginfo == SplitDebuginfo::Unpacked
+        && !cfg!(target_os = "macos")
+        && !info.toolchain.is_emcc()
+    {
+        cmd.arg("-gsplit-dwarf"); // a '.dwo' file next to every object, objects on macos are unpacked already
+    }
+    if info.settings.dead_code_elimination || info.settings.icf {
+        // the linker discards and folds sections, so every function and variable gets its own
+        cmd.args(["-ffunction-sections", "-fdata-sections"]);
     }
     match info.settings.warn_level {
         WarnLevel::None => {
@@ -97,6 +117,13 @@ pub(super) fn compile(src: &Path, obj: &Path, info: &BuildInfo, pch: &PreCompHea
             cmd.arg("-fno-exceptions"); // exceptions on by default
         }
     }
+    if info.settings.visibility == Visibility::Hidden && !cfg!(windows) {
+        // DLLs only ever export what is marked '__declspec(dllexport)'
+        cmd.arg("-fvisibility=hidden");
+        if info.lang.is_cpp() {
+            cmd.arg("-fvisibility-inlines-hidden");
+        }
+    }
     if info.settings.pthreads {
         cmd.arg("-pthread");
     }
@@ -150,6 +177,12 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
     let mut cmd = info.toolchain.linker(info.lang.is_cpp() || info.cpprt); // use g++/clang++ etc. when combining C and C++
 
     cmd.args(info.link_args);
+    match info.settings.linker {
+        Linker::Default => (),
+        linker => {
+            cmd.arg(format!("-fuse-ld={linker}"));
+        }
+    }
     if let ProjKind::SharedLib { implib } = info.projkind {
         if cfg!(target_os = "macos") {
             cmd.arg("-dynamiclib");
@@ -157,7 +190,23 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
             cmd.arg("-shared");
         }
         if implib {
-            cmd.arg(format!("-Wl,--out-implib,{}", info.implib.unwrap().display())); // forward to LINK.exe
+            cmd.arg(format!("-Wl,--out-implib,{}", info.implib.as_ref().unwrap().display())); // forward to LINK.exe
+        }
+    }
+    let versioned = match info.projkind {
+        ProjKind::SharedLib { .. } => super::versioned_shared_lib(&info.outfile, info.version, info.toolchain),
+        _ => None,
+    };
+    if let Some((_, soname)) = &versioned {
+        let soname = soname.file_name().unwrap().to_string_lossy();
+        if cfg!(target_os = "macos") {
+            // consumers record '@rpath/libname.1.dylib', and find it through their rpaths
+            let v = info.version;
+            cmd.arg(format!("-Wl,-install_name,@rpath/{soname}"));
+            cmd.arg(format!("-Wl,-compatibility_version,{}.0.0", v.major));
+            cmd.arg(format!("-Wl,-current_version,{v}"));
+        } else {
+            cmd.arg(format!("-Wl,-soname,{soname}"));
         }
     }
     if !info.toolchain.is_emcc() {
@@ -185,6 +234,31 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
     if info.settings.opt_linktime {
         cmd.arg("-flto");
     }
+    // packed debug info is stripped once it has been copied out of the binary (see 'split_debuginfo')
+    if !info.toolchain.is_emcc() && !packs_debuginfo(&info.settings, info.toolchain) {
+        match info.settings.strip {
+            Strip::None => (),
+            Strip::Debuginfo => {
+                cmd.arg("-Wl,-S");
+            }
+            Strip::Symbols if cfg!(target_os = "macos") => {
+                cmd.arg("-Wl,-S,-x"); // exported symbols stay, local ones go
+            }
+            Strip::Symbols => {
+                cmd.arg("-s");
+            }
+        }
+    }
+    if info.settings.dead_code_elimination && !info.toolchain.is_emcc() {
+        if cfg!(target_os = "macos") {
+            cmd.arg("-Wl,-dead_strip");
+        } else {
+            cmd.arg("-Wl,--gc-sections");
+        }
+    }
+    if info.settings.icf && info.toolchain.supports_icf(info.settings.linker) {
+        cmd.arg("-Wl,--icf=all");
+    }
     if info.settings.pthreads {
         cmd.arg("-pthread");
     }
@@ -207,8 +281,25 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
     cmd.args(objs);
     cmd.args(info.libdirs.iter().map(|l| format!("-L{}", l.display())));
     cmd.args(info.rpaths.iter().map(|l| format!("-Wl,-rpath,{}", l.display())));
+    if !cfg!(windows) && !info.toolchain.is_emcc() {
+        // shared libraries of dependencies are found relative to the binary (DLLs have no rpaths)
+        let rpaths = super::runtime_dirs(&info.shared_libs);
+        // searched first, so that an installed binary loads the libraries installed with it ('vango install')
+        if !rpaths.is_empty() && !info.is_testexe {
+            cmd.arg(format!("-Wl,-rpath,{}", super::install_rpath(info.projkind)));
+        }
+        cmd.args(
+            rpaths
+                .iter()
+                .map(|d| format!("-Wl,-rpath,{}", super::origin_rpath(&info.outdir, d))),
+        );
+    }
     cmd.args(info.archives.iter().map(|l| format!("-l{}", l.display())));
-    cmd.arg(format!("-o{}", info.outfile.display()));
+    if let Some((file, _)) = &versioned {
+        cmd.arg(format!("-o{}", file.display()));
+    } else {
+        cmd.arg(format!("-o{}", info.outfile.display()));
+    }
     if verbose {
         cmd.arg("--verbose");
     }
@@ -217,6 +308,15 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
         print_command(&cmd);
     }
     if output::gnu_linker(&cmd.output().map_err(|_| Error::LinkerNotFound(info.toolchain))?) {
+        if packs_debuginfo(&info.settings, info.toolchain) {
+            let binary = versioned.as_ref().map_or(&info.outfile, |(file, _)| file);
+            split_debuginfo(binary, &info.settings, info.toolchain, echo)?;
+        }
+        // 'libname.so' links to the soname, which links to the file
+        if let Some((file, soname)) = &versioned {
+            super::relink_shared_lib(file, soname)?;
+            super::relink_shared_lib(soname, &info.outfile)?;
+        }
         log_info_ln!("successfully built project: {}\n", info.outfile.display());
         Ok(())
     } else {
@@ -227,11 +327,18 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: boo
 pub(super) fn archive(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose: bool) -> Result<(), Error> {
     let mut cmd = info.toolchain.archiver();
 
-    if verbose {
-        cmd.arg("rcsv");
-    } else {
-        cmd.arg("rcs");
-    }
+    // thin archives reference the objects in the output directory instead of copying them, an archive
+    // in the other format cannot be updated
+    let thin = info.settings.thin_archive && info.toolchain.supports_thin_archive();
+    if info.outfile.exists() && super::is_thin_archive(&info.outfile) != thin {
+        std::fs::remove_file(&info.outfile)?;
+    }
+    match (thin, verbose) {
+        (false, false) => cmd.arg("rcs"),
+        (false, true) => cmd.arg("rcsv"),
+        (true, false) => cmd.arg("rcsT"),
+        (true, true) => cmd.arg("rcsTv"),
+    };
     cmd.arg(&info.outfile);
     cmd.args(info.link_args);
     cmd.args(objs);
@@ -247,6 +354,50 @@ pub(super) fn archive(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, verbose:
     }
 }
 
+fn packs_debuginfo(settings: &BuildSettings, toolchain: ToolChain) -> bool {
+    settings.debug_info != DebugInfo::None && settings.split_debuginfo == SplitDebuginfo::Packed && !toolchain.is_emcc()
+}
+
+/// moves the debug info of a linked binary to 'binary.debug' (found through its '.gnu_debuglink' section),
+/// or 'binary.dSYM' on macos, and strips the binary
+fn split_debuginfo(binary: &Path, settings: &BuildSettings, toolchain: ToolChain, echo: bool) -> Result<(), Error> {
+    let symbols = settings.strip == Strip::Symbols;
+    let mut steps = Vec::new();
+    if cfg!(target_os = "macos") {
+        let mut dsym = std::process::Command::new("dsymutil");
+        dsym.arg(binary).arg("-o").arg(format!("{}.dSYM", binary.display()));
+        let mut strip = std::process::Command::new("strip");
+        strip.arg("-S");
+        if symbols {
+            strip.arg("-x");
+        }
+        strip.arg(binary);
+        steps.extend([dsym, strip]);
+    } else {
+        let debug = format!("{}.debug", binary.display());
+        let mut keep = toolchain.objcopy();
+        keep.arg("--only-keep-debug").arg(binary).arg(&debug);
+        let mut strip = toolchain.objcopy();
+        strip.arg(if symbols { "--strip-all" } else { "--strip-debug" });
+        strip.arg(format!("--add-gnu-debuglink={debug}")).arg(binary);
+        steps.extend([keep, strip]);
+    }
+    for mut step in steps {
+        if echo {
+            print_command(&step);
+        }
+        let program = step.get_program().to_string_lossy().to_string();
+        let output = step.output().map_err(|_| Error::StripToolNotFound(program))?;
+        if !output.status.success() {
+            return Err(Error::StripFail(
+                binary.to_path_buf(),
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn print_command(cmd: &std::process::Command) {
     print!("{} ", cmd.get_program().display());
     for arg in cmd.get_args() {
@@ -281,7 +432,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-c",
                     "-O0",
@@ -298,7 +448,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-fpie",
                     "-c",
@@ -334,7 +483,6 @@ mod tests {
                 cmd,
                 [
                     "--target=x86_64-w64-mingw32",
-                    "-H",
                     "-std=c++23",
                     "-c",
                     "-O0",
@@ -351,7 +499,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++23",
                     "-fpie",
                     "-c",
@@ -386,7 +533,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-c",
                     "-O3",
@@ -403,7 +549,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-fpie",
                     "-c",
@@ -438,7 +583,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++23",
                     "-c",
                     "-O3",
@@ -455,7 +599,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++23",
                     "-fpie",
                     "-c",
@@ -490,7 +633,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-c",
                     "-O0",
@@ -507,7 +649,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-fPIC",
                     "-c",
@@ -549,7 +690,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-c",
                     "-O0",
@@ -566,7 +706,6 @@ mod tests {
             assert_eq!(
                 cmd,
                 [
-                    "-H",
                     "-std=c++20",
                     "-fPIC",
                     "-c",
@@ -580,4 +719,104 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    pub fn versioned_sharedlib_names() {
+        let out = PathBuf::from("bin/release/libfoo").with_extension(ToolChain::shared_lib_ext());
+        let version = crate::config::Version {
+            major: 2,
+            minor: 3,
+            patch: 4,
+        };
+        let names = crate::exec::versioned_shared_lib(&out, version, ToolChain::Gcc);
+        if cfg!(windows) {
+            assert_eq!(names, None);
+        } else if cfg!(target_os = "macos") {
+            assert_eq!(
+                names,
+                Some((
+                    PathBuf::from("bin/release/libfoo.2.3.4.dylib"),
+                    PathBuf::from("bin/release/libfoo.2.dylib")
+                ))
+            );
+        } else {
+            assert_eq!(
+                names,
+                Some((
+                    PathBuf::from("bin/release/libfoo.so.2.3.4"),
+                    PathBuf::from("bin/release/libfoo.so.2")
+                ))
+            );
+        }
+        assert_eq!(crate::exec::versioned_shared_lib(&out, version, ToolChain::Emcc), None);
+    }
+
+    #[test]
+    pub fn origin_relative_rpaths() {
+        use crate::exec::{relative_path, runtime_dirs};
+        let root = std::env::temp_dir();
+        let app = root.join("app/bin/debug");
+        assert_eq!(
+            relative_path(&app, &root.join("shl/bin/debug")),
+            PathBuf::from("../../../shl/bin/debug")
+        );
+        assert_eq!(relative_path(&app, &app.join("deps")), PathBuf::from("deps"));
+        assert_eq!(relative_path(&app, &app), PathBuf::new());
+
+        let libs = [
+            PathBuf::from("../shl/bin/debug/libshl.so"),
+            PathBuf::from("../shl/bin/debug/libshl.so.1"),
+            PathBuf::from("../dep/lib/libdep.so"),
+        ];
+        assert_eq!(
+            runtime_dirs(&libs),
+            [PathBuf::from("../shl/bin/debug"), PathBuf::from("../dep/lib")]
+        );
+        let origin = if cfg!(target_os = "macos") { "@loader_path" } else { "$ORIGIN" };
+        assert_eq!(crate::exec::origin_rpath(&app, &app), origin);
+        assert_eq!(
+            crate::exec::origin_rpath(&app, &root.join("app/lib")),
+            format!("{origin}/../../lib")
+        );
+        assert_eq!(crate::exec::install_rpath(ProjKind::App), format!("{origin}/../lib"));
+        assert_eq!(crate::exec::install_rpath(ProjKind::SharedLib { implib: true }), origin);
+    }
+
+    #[test]
+    pub fn compile_cmd_debuginfo_levels() {
+        use crate::config::{DebugInfo, SplitDebuginfo};
+        let src = PathBuf::from("src/main.cpp");
+        let obj = PathBuf::from("bin/release/obj/main.o");
+        let args = |toolchain, debug_info, split_debuginfo| {
+            let mut info = BuildInfo::mock_release(Path::new("bin/release"), ProjKind::App, Lang::Cpp(20), toolchain, None, false);
+            info.settings.debug_info = debug_info;
+            info.settings.split_debuginfo = split_debuginfo;
+            let cmd = super::compile(&src, &obj, &info, &PreCompHead::None, false, false);
+            cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect::<Vec<_>>()
+        };
+
+        let gcc = args(ToolChain::Gcc, DebugInfo::LineTablesOnly, SplitDebuginfo::Off);
+        assert!(gcc.contains(&"-g1".to_string()) && !gcc.contains(&"-g".to_string()));
+        let clang = args(ToolChain::ClangGnu, DebugInfo::LineTablesOnly, SplitDebuginfo::Packed);
+        assert!(clang.contains(&"-gline-tables-only".to_string()) && !clang.contains(&"-gsplit-dwarf".to_string()));
+        let unpacked = args(ToolChain::Gcc, DebugInfo::Full, SplitDebuginfo::Unpacked);
+        assert_eq!(unpacked.contains(&"-gsplit-dwarf".to_string()), !cfg!(target_os = "macos"));
+        let none = args(ToolChain::Gcc, DebugInfo::None, SplitDebuginfo::Unpacked);
+        assert!(!none.iter().any(|a| a.starts_with("-g")));
+    }
+
+    #[test]
+    pub fn compile_cmd_sections() {
+        let src = PathBuf::from("src/main.cpp");
+        let obj = PathBuf::from("bin/release/obj/main.o");
+        let sections = ["-ffunction-sections".to_string(), "-fdata-sections".to_string()];
+        let mut info = BuildInfo::mock_release(Path::new("bin/release"), ProjKind::App, Lang::Cpp(20), ToolChain::Gcc, None, false);
+        for (dce, icf, expected) in [(false, false, false), (true, false, true), (false, true, true)] {
+            info.settings.dead_code_elimination = dce;
+            info.settings.icf = icf;
+            let cmd = super::compile(&src, &obj, &info, &PreCompHead::None, false, false);
+            let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy().to_string()).collect();
+            assert_eq!(args.windows(2).any(|w| w == sections), expected);
+        }
+    }
 }
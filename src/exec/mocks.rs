@@ -1,5 +1,5 @@
 use super::*;
-use crate::config::{Runtime, WarnLevel};
+use crate::config::{DebugInfo, Linker, Runtime, SplitDebuginfo, Strip, Visibility, WarnLevel};
 
 impl BuildInfo {
     pub fn mock_base(outfile: &Path) -> Self {
@@ -24,11 +24,18 @@ impl BuildInfo {
                 iso_compliant: false,
                 warn_level: WarnLevel::Basic,
                 warn_as_error: false,
-                debug_info: true,
+                debug_info: DebugInfo::Full,
+                strip: Strip::None,
+                split_debuginfo: SplitDebuginfo::Off,
                 runtime: Runtime::DynamicDebug,
                 aslr: true,
                 no_rtti: false,
                 no_except: false,
+                visibility: Visibility::Default,
+                linker: Linker::Default,
+                thin_archive: false,
+                dead_code_elimination: false,
+                icf: false,
 
                 pthreads: false,
                 asan: false,
@@ -47,8 +54,14 @@ impl BuildInfo {
             headers: vec![],
             archives: vec![],
             relink: vec![],
+            shared_libs: vec![],
             outfile: outfile.to_owned(),
             implib: None,
+            version: Version {
+                major: 0,
+                minor: 1,
+                patch: 0,
+            },
 
             comp_args: vec![],
             link_args: vec![],
@@ -86,7 +99,7 @@ impl BuildInfo {
             settings: BuildSettings {
                 opt_level: 3,
                 opt_linktime: true,
-                debug_info: false,
+                debug_info: DebugInfo::None,
                 runtime: if crtstatic {
                     Runtime::StaticRelease
                 } else {
@@ -8,7 +8,7 @@ pub mod prep;
 mod queue;
 
 use crate::{
-    config::{BuildSettings, Lang, ProjKind, ToolChain},
+    config::{BuildSettings, Lang, ProjKind, ToolChain, Version},
     error::Error,
     log_info_ln, log_warn_ln,
 };
@@ -38,8 +38,12 @@ pub struct BuildInfo {
     pub headers: Vec<PathBuf>,
     pub archives: Vec<PathBuf>,
     pub relink: Vec<PathBuf>,
+    /// shared libraries of dependencies, found through rpaths relative to 'outdir' (see 'origin_rpath')
+    pub shared_libs: Vec<PathBuf>,
     pub outfile: PathBuf,
     pub implib: Option<PathBuf>,
+    /// shared libraries are versioned on unix (see 'versioned_shared_lib')
+    pub version: Version,
 
     pub comp_args: Vec<String>,
     pub link_args: Vec<String>,
@@ -53,6 +57,149 @@ enum PreCompHead<'a> {
     Use(&'a Path),
 }
 
+/// the file a versioned shared library is written to and its soname (install name on macos), next to 'outfile',
+/// which links to the soname: 'libname.so.1.2.3' and 'libname.so.1' ('libname.1.2.3.dylib' and 'libname.1.dylib'),
+/// DLLs and wasm side modules are not versioned
+pub fn versioned_shared_lib(outfile: &Path, version: Version, toolchain: ToolChain) -> Option<(PathBuf, PathBuf)> {
+    if cfg!(windows) || toolchain.is_emcc() {
+        return None;
+    }
+    let Version { major, minor, patch } = version;
+    let file = outfile.file_name()?.to_string_lossy();
+    if cfg!(target_os = "macos") {
+        let stem = file.strip_suffix(".dylib").unwrap_or(&file);
+        Some((
+            outfile.with_file_name(format!("{stem}.{major}.{minor}.{patch}.dylib")),
+            outfile.with_file_name(format!("{stem}.{major}.dylib")),
+        ))
+    } else {
+        Some((
+            outfile.with_file_name(format!("{file}.{major}.{minor}.{patch}")),
+            outfile.with_file_name(format!("{file}.{major}")),
+        ))
+    }
+}
+
+/// replaces 'link' with a symbolic link to 'target' (a file in the same directory)
+#[cfg(unix)]
+pub fn relink_shared_lib(target: &Path, link: &Path) -> Result<(), Error> {
+    match std::fs::symlink_metadata(link) {
+        Ok(_) => std::fs::remove_file(link)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => (),
+        Err(e) => return Err(e.into()),
+    }
+    std::os::unix::fs::symlink(target.file_name().unwrap(), link)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn relink_shared_lib(target: &Path, link: &Path) -> Result<(), Error> {
+    let _ = std::fs::remove_file(link);
+    std::fs::copy(target, link)?;
+    Ok(())
+}
+
+/// copies a static library, thin archives ('thin-archive') only reference objects in the build tree, so
+/// their copy is archived again from these objects
+pub fn copy_archive(src: &Path, dest: &Path, toolchain: ToolChain) -> Result<(), Error> {
+    let _ = std::fs::remove_file(dest);
+    if !is_thin_archive(src) {
+        std::fs::copy(src, dest)?;
+        return Ok(());
+    }
+    // members are listed relative to the working directory
+    let list = toolchain
+        .archiver()
+        .arg("t")
+        .arg(src)
+        .output()
+        .map_err(|_| Error::ArchiverNotFound(toolchain))?;
+    let mut cmd = toolchain.archiver();
+    cmd.arg("rcs").arg(dest);
+    cmd.args(String::from_utf8_lossy(&list.stdout).lines());
+    if list.status.success() && cmd.output().map_err(|_| Error::ArchiverNotFound(toolchain))?.status.success() {
+        Ok(())
+    } else {
+        Err(Error::ArchiverFail(src.to_path_buf()))
+    }
+}
+
+fn is_thin_archive(path: &Path) -> bool {
+    use std::io::Read;
+    let mut magic = [0u8; 8];
+    std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && &magic == b"!<thin>\n"
+}
+
+/// the directories of a set of shared libraries, in first-seen order
+pub fn runtime_dirs(shared_libs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in shared_libs.iter().filter_map(|s| s.parent()) {
+        if !dirs.iter().any(|d| d == dir) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// an rpath to 'dir' relative to the binary in 'outdir' ('$ORIGIN/../lib', '@loader_path/../lib' on macos),
+/// so that executables keep finding their libraries when the tree they were built in is moved
+pub fn origin_rpath(outdir: &Path, dir: &Path) -> String {
+    let resolve = |p: &Path| {
+        std::fs::canonicalize(p)
+            .or_else(|_| std::path::absolute(p))
+            .unwrap_or(p.to_path_buf())
+    };
+    let relative = relative_path(&resolve(outdir), &resolve(dir));
+    if relative.as_os_str().is_empty() {
+        origin().to_string()
+    } else {
+        format!("{}/{}", origin(), relative.display())
+    }
+}
+
+/// the rpath of an installed binary to the shared libraries installed with it into 'lib' of the prefix
+pub fn install_rpath(kind: ProjKind) -> String {
+    match kind {
+        ProjKind::App => format!("{}/../lib", origin()),
+        _ => origin().to_string(),
+    }
+}
+
+fn origin() -> &'static str {
+    if cfg!(target_os = "macos") { "@loader_path" } else { "$ORIGIN" }
+}
+
+/// the path from one absolute directory to another
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let (from, to): (Vec<_>, Vec<_>) = (from.components().collect(), to.components().collect());
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    std::iter::repeat_n(std::path::Component::ParentDir, from.len() - common)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+/// the environment variable the dynamic loader searches for shared libraries, with the directories of
+/// 'shared_libs' prepended (for running executables that were not linked with rpaths, i.e. on windows)
+pub fn runtime_env(shared_libs: &[PathBuf]) -> Option<(&'static str, std::ffi::OsString)> {
+    let var = if cfg!(windows) {
+        "PATH"
+    } else if cfg!(target_os = "macos") {
+        "DYLD_LIBRARY_PATH"
+    } else {
+        "LD_LIBRARY_PATH"
+    };
+    let dirs = runtime_dirs(shared_libs);
+    if dirs.is_empty() {
+        return None;
+    }
+    let existing = std::env::var_os(var).unwrap_or_default();
+    let paths = dirs
+        .into_iter()
+        .map(|d| std::path::absolute(&d).unwrap_or(d))
+        .chain(std::env::split_paths(&existing));
+    std::env::join_paths(paths).ok().map(|value| (var, value))
+}
+
 fn on_compile_finish(tc: ToolChain, output: &std::process::Output) -> bool {
     match tc {
         ToolChain::Msvc => output::msvc_compiler(output),
@@ -1,7 +1,7 @@
 use super::{BuildInfo, PreCompHead};
 use crate::{
     Error,
-    config::{Lang, ProjKind, Runtime, WarnLevel},
+    config::{DebugInfo, Lang, ProjKind, Runtime, Strip, WarnLevel},
     exec::output,
     log_info_ln,
 };
@@ -85,8 +85,17 @@ pub(super) fn compile(src: &Path, obj: &Path, info: &BuildInfo, pch: &PreCompHea
     if info.settings.opt_linktime && !info.is_testexe {
         cmd.arg("/GL");
     }
-    if info.settings.debug_info {
-        cmd.args(["/Zi", "/FS", "/sdl"]); // debug info, thread safe, extra security
+    if info.settings.dead_code_elimination || info.settings.icf {
+        cmd.args(["/Gy", "/Gw"]); // functions and globals as separate COMDATs, for /OPT:REF and /OPT:ICF
+    }
+    if info.settings.debug_info != DebugInfo::None {
+        // PDBs always hold the debug info, 'split-debuginfo' has nothing to split
+        if info.settings.debug_info == DebugInfo::LineTablesOnly && info.toolchain.is_clang() {
+            cmd.arg("/Zd"); // line tables only, cl.exe has no equivalent
+        } else {
+            cmd.arg("/Zi");
+        }
+        cmd.args(["/FS", "/sdl"]); // thread safe, extra security
         cmd.arg(format!("/Fd:{}\\", info.outdir.display())); // PDB output dir
         if !info.toolchain.is_clang() {
             cmd.arg("/Zf");
@@ -119,6 +128,7 @@ pub(super) fn compile(src: &Path, obj: &Path, info: &BuildInfo, pch: &PreCompHea
             cmd.arg("/EHsc"); // default C++ exception handling, extern "C" -> noexcept
         }
     }
+    // DLLs only export what is marked '__declspec(dllexport)', 'visibility' has no equivalent
     if info.settings.asan {
         cmd.arg("-fsanitize=address");
     }
@@ -174,12 +184,24 @@ pub(super) fn link(objs: Vec<PathBuf>, info: BuildInfo, echo: bool, _verbose: bo
     if info.settings.aslr {
         cmd.arg("/DYNAMICBASE");
     }
-    if info.settings.debug_info {
+    if info.settings.debug_info != DebugInfo::None {
         cmd.arg("/DEBUG");
+        // binaries never contain debug info, stripping keeps the build path out of them and publishes symbols only
+        if info.settings.strip != Strip::None {
+            cmd.arg("/PDBALTPATH:%_PDB%");
+        }
+        if info.settings.strip == Strip::Symbols {
+            cmd.arg(format!("/PDBSTRIPPED:{}", info.outfile.with_extension("stripped.pdb").display()));
+        }
     }
     if info.settings.opt_linktime && !info.is_testexe {
         cmd.arg("/LTCG"); // link-time codegen, iff /GL
         cmd.arg("/OPT:REF"); // strip unreferenced symbols
+    } else if info.settings.dead_code_elimination {
+        cmd.arg("/OPT:REF");
+    }
+    if info.settings.icf {
+        cmd.arg("/OPT:ICF");
     }
     if info.settings.warn_as_error {
         cmd.arg("/WX");
@@ -0,0 +1,370 @@
+use super::Resolver;
+use crate::{error::Error, log_info_ln};
+use sha2::{Digest, Sha256};
+use std::{
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    TarGz,
+    Tar,
+    Zip,
+}
+
+impl Format {
+    fn from_location(location: &str) -> Self {
+        let location = location.to_ascii_lowercase();
+        if location.ends_with(".zip") {
+            Self::Zip
+        } else if location.ends_with(".tar") {
+            Self::Tar
+        } else {
+            Self::TarGz
+        }
+    }
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// true for http(s) urls, anything else is a 'file://' url or a local path
+pub fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// reads an archive from an http(s) url (via curl), a 'file://' url, or a path relative to 'base'
+pub fn download(base: &Path, location: &str) -> Result<Vec<u8>, Error> {
+    if is_remote(location) {
+        let output = std::process::Command::new("curl")
+            .args(["--fail", "--silent", "--show-error", "--location", location])
+            .output()
+            .map_err(|_| Error::DownloadFail(location.to_string()))?;
+        if !output.status.success() {
+            return Err(Error::DownloadFail(location.to_string()));
+        }
+        Ok(output.stdout)
+    } else {
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        std::fs::read(base.join(path)).map_err(|_| Error::DownloadFail(location.to_string()))
+    }
+}
+
+/// archives are cached per checksum and extraction layout, so that changing the checksum always fetches again, and
+/// changing 'strip-prefix' extracts again
+fn cache_path(location: &str, sha256: &str, strip_prefix: usize, manifest: Option<&[u8]>) -> PathBuf {
+    std::env::home_dir()
+        .unwrap()
+        .join(".vango/packages")
+        .join(vendor_path(location, sha256, strip_prefix, manifest))
+}
+
+/// location of an archive dependency relative to the vendor directory, an injected manifest is part of the key so that
+/// projects injecting different manifests never share an extraction
+pub fn vendor_path(location: &str, sha256: &str, strip_prefix: usize, manifest: Option<&[u8]>) -> PathBuf {
+    let file = location.rsplit(['/', '\\']).next().unwrap_or("archive");
+    let stem = file.split('.').next().filter(|s| !s.is_empty()).unwrap_or("archive");
+    let mut key = format!("{stem}-{}", &sha256[..sha256.len().min(16)]);
+    if strip_prefix > 0 {
+        key.push_str(&format!("-strip{strip_prefix}"));
+    }
+    if let Some(manifest) = manifest {
+        key.push_str(&format!("-m{}", &sha256_hex(manifest)[..16]));
+    }
+    PathBuf::from("archive").join(key)
+}
+
+/// reads the manifest a project injects into an archive dependency
+pub fn read_injected(manifest: &Path) -> Result<Vec<u8>, Error> {
+    std::fs::read(manifest).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => Error::MissingBuildScript(manifest.to_path_buf()),
+        _ => Error::FileSystem(e),
+    })
+}
+
+/// downloads, verifies and extracts an archive dependency into the package cache, unless already cached
+pub fn resolve(
+    name: &str,
+    location: &str,
+    sha256: &str,
+    strip_prefix: usize,
+    manifest: Option<&Path>,
+    resolver: &Resolver,
+) -> Result<PathBuf, Error> {
+    let sha256 = sha256.to_ascii_lowercase();
+    let manifest = manifest.map(read_injected).transpose()?;
+    let manifest = manifest.as_deref();
+    if let Some(vendor) = &resolver.vendor {
+        let path = vendor.join(vendor_path(location, &sha256, strip_prefix, manifest));
+        if !std::fs::exists(&path)? {
+            return Err(Error::NotVendored(name.to_string()));
+        }
+        return Ok(path);
+    }
+
+    let path = cache_path(location, &sha256, strip_prefix, manifest);
+    if !std::fs::exists(&path)? {
+        if resolver.offline {
+            return Err(Error::OfflineFetch(name.to_string()));
+        }
+        log_info_ln!("{:-<80}", format!("downloading archive dependency '{name}' "));
+        let data = download(&std::env::current_dir()?, location)?;
+        verify(name, &data, &sha256)?;
+        unpack(&data, Format::from_location(location), strip_prefix, manifest, &path)?;
+    }
+    Ok(path)
+}
+
+pub fn verify(name: &str, data: &[u8], sha256: &str) -> Result<(), Error> {
+    let actual = sha256_hex(data);
+    if actual == sha256 {
+        Ok(())
+    } else {
+        Err(Error::ChecksumMismatch(name.to_string(), sha256.to_string(), actual))
+    }
+}
+
+/// extracts into a staging directory first, so that a failed extraction never leaves a broken cache entry behind
+pub fn unpack_tar_gz(data: &[u8], dest: &Path) -> Result<(), Error> {
+    unpack(data, Format::TarGz, 0, None, dest)
+}
+
+fn unpack(data: &[u8], format: Format, strip_prefix: usize, manifest: Option<&[u8]>, dest: &Path) -> Result<(), Error> {
+    let staging = dest.with_file_name(format!("{}.partial", dest.file_name().unwrap().to_string_lossy()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging)?;
+    let mut result = match format {
+        Format::TarGz => unpack_tar(tar::Archive::new(flate2::read::GzDecoder::new(data)), strip_prefix, &staging),
+        Format::Tar => unpack_tar(tar::Archive::new(data), strip_prefix, &staging),
+        Format::Zip => unpack_zip(data, strip_prefix, &staging),
+    };
+    // injected before the extraction is published, so that it is never seen without its manifest
+    if let (Ok(()), Some(manifest)) = (&result, manifest) {
+        result = std::fs::write(staging.join("Vango.toml"), manifest).map_err(Error::FileSystem);
+    }
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    std::fs::rename(&staging, dest)?;
+    Ok(())
+}
+
+/// removes the leading 'strip' components of an archive entry, rejects entries escaping the destination
+fn strip_path(path: &Path, strip: usize) -> Option<PathBuf> {
+    let mut components = path.components().filter(|c| *c != Component::CurDir);
+    if components.clone().any(|c| !matches!(c, Component::Normal(_))) {
+        return None;
+    }
+    let stripped: PathBuf = components.by_ref().skip(strip).collect();
+    (!stripped.as_os_str().is_empty()).then_some(stripped)
+}
+
+/// whether a path stays inside 'root' once the links extracted so far are followed, the part of the path that does not
+/// exist yet may not step out with '..'
+fn resolves_inside(root: &Path, path: &Path) -> bool {
+    let Some(existing) = path.ancestors().find(|a| a.exists()) else {
+        return false;
+    };
+    let rest = path.strip_prefix(existing).unwrap();
+    rest.components().all(|c| matches!(c, Component::Normal(_))) && std::fs::canonicalize(existing).is_ok_and(|p| p.starts_with(root))
+}
+
+/// links are extracted only if they point inside the destination, and nothing is written through a link, so that a
+/// link followed by an entry below it ('a -> /etc', 'a/passwd') can not escape
+fn unpack_tar<R: Read>(mut archive: tar::Archive<R>, strip_prefix: usize, dest: &Path) -> Result<(), Error> {
+    let root = std::fs::canonicalize(dest)?;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(path) = strip_path(&entry.path()?, strip_prefix) else {
+            continue;
+        };
+        let target = dest.join(&path);
+        let parent = target.parent().unwrap();
+        if !resolves_inside(&root, parent) {
+            return Err(Error::UnsafeArchiveEntry(path));
+        }
+        std::fs::create_dir_all(parent)?;
+
+        let kind = entry.header().entry_type();
+        if kind.is_hard_link() {
+            // hard links name another entry of the archive, which is stripped like any other path
+            let source = entry.link_name()?.and_then(|l| strip_path(&l, strip_prefix));
+            match source.map(|s| dest.join(s)) {
+                Some(source) if resolves_inside(&root, &source) && source.is_file() => {
+                    let _ = std::fs::remove_file(&target);
+                    std::fs::hard_link(source, &target)?;
+                }
+                _ => return Err(Error::UnsafeArchiveEntry(path)),
+            }
+        } else if kind.is_symlink() {
+            match entry.link_name()? {
+                Some(link) if link.is_relative() && resolves_inside(&root, &parent.join(&link)) => {
+                    entry.unpack(&target)?;
+                }
+                _ => return Err(Error::UnsafeArchiveEntry(path)),
+            }
+        } else {
+            entry.unpack(&target)?;
+        }
+    }
+    Ok(())
+}
+
+fn unpack_zip(data: &[u8], strip_prefix: usize, dest: &Path) -> Result<(), Error> {
+    let invalid = |e: zip::result::ZipError| Error::FileSystem(std::io::Error::new(std::io::ErrorKind::InvalidData, e));
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data)).map_err(invalid)?;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(invalid)?;
+        let Some(path) = file.enclosed_name().and_then(|p| strip_path(&p, strip_prefix)) else {
+            continue;
+        };
+        let target = dest.join(path);
+        if file.is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut file, &mut std::fs::File::create(&target)?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vango-test-archive-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn tar_gz(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+        for (path, body) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(body.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, body.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn strip_components() {
+        assert_eq!(strip_path(Path::new("sdk-1.2/include/sdk.h"), 1), Some(PathBuf::from("include/sdk.h")));
+        assert_eq!(strip_path(Path::new("./sdk-1.2/lib"), 2), None);
+        assert_eq!(strip_path(Path::new("../evil"), 0), None);
+        assert_eq!(strip_path(Path::new("/etc/passwd"), 0), None);
+    }
+
+    #[test]
+    fn unpack_formats() {
+        let dir = scratch_dir("unpack");
+        let data = tar_gz(&[("sdk-1.2/Vango.toml", "[staticlib]"), ("sdk-1.2/include/sdk.h", "#pragma once")]);
+        unpack(&data, Format::TarGz, 1, None, &dir.join("tgz")).unwrap();
+        assert!(dir.join("tgz/Vango.toml").exists());
+        assert!(dir.join("tgz/include/sdk.h").exists());
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("sdk/lib/libsdk.a", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"!<arch>").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+        unpack(&data, Format::Zip, 1, Some(b"[staticlib]"), &dir.join("zip")).unwrap();
+        assert!(dir.join("zip/lib/libsdk.a").exists());
+        assert_eq!(std::fs::read_to_string(dir.join("zip/Vango.toml")).unwrap(), "[staticlib]");
+
+        assert!(unpack(b"garbage", Format::Zip, 0, None, &dir.join("bad")).is_err());
+        assert!(!dir.join("bad").exists());
+        assert!(!dir.join("bad.partial").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn tar_links(entries: &[(&str, tar::EntryType, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, kind, link) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(*kind);
+            header.set_mode(0o644);
+            if kind.is_file() {
+                header.set_size(link.len() as u64);
+                builder.append_data(&mut header, path, link.as_bytes()).unwrap();
+            } else {
+                header.set_size(0);
+                builder.append_link(&mut header, path, link).unwrap();
+            }
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn unpack_links() {
+        if cfg!(windows) {
+            return;
+        }
+        use tar::EntryType::{Link, Regular, Symlink};
+        let dir = scratch_dir("links");
+        let outside = dir.join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+
+        let data = tar_links(&[
+            ("sdk/lib/libsdk.so.1", Regular, "elf"),
+            ("sdk/lib/libsdk.so", Symlink, "libsdk.so.1"),
+            ("sdk/lib/libsdk-copy.so", Link, "sdk/lib/libsdk.so.1"),
+            ("sdk/include", Symlink, "lib"),
+        ]);
+        unpack(&data, Format::Tar, 1, None, &dir.join("ok")).unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("ok/lib/libsdk.so")).unwrap(), "elf");
+        assert_eq!(std::fs::read_to_string(dir.join("ok/lib/libsdk-copy.so")).unwrap(), "elf");
+
+        let rejected = |data: &[u8], name: &str| {
+            let result = unpack(data, Format::Tar, 0, None, &dir.join(name));
+            matches!(result, Err(Error::UnsafeArchiveEntry(_)))
+        };
+        // a link out of the destination, followed by an entry written through it
+        let escape = outside.to_string_lossy().to_string();
+        let data = tar_links(&[("a", Symlink, &escape), ("a/file", Regular, "pwned")]);
+        assert!(rejected(&data, "abs"));
+        let data = tar_links(&[("d/s", Symlink, ".."), ("t", Symlink, "d/s/.."), ("t/file", Regular, "pwned")]);
+        assert!(rejected(&data, "rel"));
+        let data = tar_links(&[("h", Link, "../outside/secret")]);
+        assert!(rejected(&data, "hard"));
+        assert!(!outside.join("file").exists());
+        assert!(!dir.join("abs").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cache_keys() {
+        let sha = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert_eq!(
+            vendor_path("https://x.org/sdk.tar.gz", sha, 0, None),
+            PathBuf::from("archive/sdk-9f86d081884c7d65")
+        );
+        assert_eq!(
+            vendor_path("https://x.org/sdk.zip", sha, 1, None),
+            PathBuf::from("archive/sdk-9f86d081884c7d65-strip1")
+        );
+        assert!(cache_path("sdk.zip", sha, 2, None).ends_with(vendor_path("sdk.zip", sha, 2, None)));
+        let a = vendor_path("sdk.zip", sha, 0, Some(b"name = 'a'"));
+        assert_ne!(a, vendor_path("sdk.zip", sha, 0, Some(b"name = 'b'")));
+        assert!(a.to_string_lossy().starts_with("archive/sdk-9f86d081884c7d65-m"));
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let data = tar_gz(&[("a.txt", "a")]);
+        assert!(verify("sdk", &data, &sha256_hex(&data)).is_ok());
+        assert!(matches!(verify("sdk", &data, &"0".repeat(64)), Err(Error::ChecksumMismatch(..))));
+    }
+}
@@ -0,0 +1,313 @@
+use super::Dependencies;
+use crate::{
+    config::{ConanOption, Lang, Profile, Runtime, ToolChain},
+    error::Error,
+    log_info_ln,
+};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// a direct conan dependency of the project being built
+pub struct ConanRequire {
+    pub reference: String,
+    pub options: BTreeMap<String, ConanOption>,
+}
+
+/// '-s' arguments describing how the project is built, anything not derived here comes from the default conan profile
+pub fn settings(toolchain: ToolChain, baseprof: &Profile, lang: Lang, runtime: Runtime) -> Result<Vec<String>, Error> {
+    let version = match toolchain {
+        // cross compilers need a dedicated conan profile
+        ToolChain::Zig | ToolChain::Emcc => None,
+        // a version left to the default profile would belong to whatever compiler that profile describes
+        _ => Some(compiler_version(toolchain, lang).ok_or(Error::ConanCompilerVersion(toolchain))?),
+    };
+    Ok(settings_with(toolchain, baseprof, lang, runtime, version.as_deref()))
+}
+
+fn settings_with(toolchain: ToolChain, baseprof: &Profile, lang: Lang, runtime: Runtime, version: Option<&str>) -> Vec<String> {
+    let mut settings = vec![format!("build_type={}", if baseprof.is_release() { "Release" } else { "Debug" })];
+    let compiler = match toolchain {
+        ToolChain::Gcc => Some("gcc"),
+        ToolChain::ClangGnu | ToolChain::ClangMsvc => Some("clang"),
+        ToolChain::Msvc => Some("msvc"),
+        ToolChain::Zig | ToolChain::Emcc => None,
+    };
+    if let Some(compiler) = compiler {
+        settings.push(format!("compiler={compiler}"));
+    }
+    if let Some(version) = version {
+        settings.push(format!("compiler.version={version}"));
+    }
+    if toolchain.is_gnu() && lang.is_cpp() {
+        settings.push(format!(
+            "compiler.libcxx={}",
+            if cfg!(target_os = "macos") { "libc++" } else { "libstdc++11" }
+        ));
+    }
+    if lang.is_cpp() && compiler.is_some() {
+        settings.push(format!("compiler.cppstd={}", lang.numeric()));
+    }
+    if toolchain.is_msvc() {
+        let (kind, config) = match runtime {
+            Runtime::StaticDebug => ("static", "Debug"),
+            Runtime::StaticRelease => ("static", "Release"),
+            Runtime::DynamicDebug => ("dynamic", "Debug"),
+            Runtime::DynamicRelease => ("dynamic", "Release"),
+        };
+        settings.push(format!("compiler.runtime={kind}"));
+        settings.push(format!("compiler.runtime_type={config}"));
+    }
+    settings
+}
+
+/// the compiler version in the form conan expects: the major version of gcc and clang (clang-cl included),
+/// and '19x' for 'cl.exe' version 19.x
+fn compiler_version(toolchain: ToolChain, lang: Lang) -> Option<String> {
+    let mut cmd = toolchain.compiler(lang.is_cpp());
+    match toolchain {
+        // 'cl.exe' prints its version in the banner on stderr, whatever the exit code
+        ToolChain::Msvc => msvc_version(&String::from_utf8_lossy(&cmd.output().ok()?.stderr)),
+        ToolChain::ClangMsvc => {
+            let output = cmd.arg("--version").output().ok().filter(|o| o.status.success())?;
+            clang_version(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => {
+            let output = cmd.arg("-dumpversion").output().ok().filter(|o| o.status.success())?;
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.trim().split('.').next().filter(|v| !v.is_empty()).map(str::to_string)
+        }
+    }
+}
+
+/// 'Microsoft (R) C/C++ Optimizing Compiler Version 19.40.33811 for x64' is '194'
+fn msvc_version(banner: &str) -> Option<String> {
+    let (_, version) = banner.split_once("Version ")?;
+    let mut parts = version.split('.');
+    let major = parts.next().filter(|p| p.chars().all(|c| c.is_ascii_digit()))?;
+    let minor = parts.next()?.chars().next().filter(char::is_ascii_digit)?;
+    Some(format!("{major}{minor}"))
+}
+
+/// 'clang version 17.0.3 (...)' is '17'
+fn clang_version(output: &str) -> Option<String> {
+    let (_, version) = output.split_once("version ")?;
+    let major: String = version.chars().take_while(char::is_ascii_digit).collect();
+    (!major.is_empty()).then_some(major)
+}
+
+/// runs 'conan install' for the direct conan dependencies of a project (missing binaries are built from source),
+/// and returns what the resolved packages require to be compiled and linked
+pub fn install(requires: &[ConanRequire], settings: &[String], dir: &Path, offline: bool, msvc: bool) -> Result<Dependencies, Error> {
+    install_with(Path::new("conan"), requires, settings, dir, offline, msvc)
+}
+
+fn install_with(
+    conan: &Path,
+    requires: &[ConanRequire],
+    settings: &[String],
+    dir: &Path,
+    offline: bool,
+    msvc: bool,
+) -> Result<Dependencies, Error> {
+    std::fs::create_dir_all(dir)?;
+    let mut conanfile = String::from("[requires]\n");
+    for req in requires {
+        let _ = writeln!(conanfile, "{}", req.reference);
+    }
+    conanfile += "\n[options]\n";
+    for req in requires {
+        let name = req.reference.split('/').next().unwrap();
+        for (key, value) in &req.options {
+            if key.contains(':') {
+                let _ = writeln!(conanfile, "{key}={value}");
+            } else {
+                let _ = writeln!(conanfile, "{name}/*:{key}={value}");
+            }
+        }
+    }
+    std::fs::write(dir.join("conanfile.txt"), conanfile)?;
+
+    log_info_ln!("{:-<80}", format!("pulling conan dependencies "));
+    let mut cmd = Command::new(conan);
+    cmd.current_dir(dir).args(["install", ".", "--format=json", "--build=missing"]);
+    for setting in settings {
+        cmd.arg("-s").arg(setting);
+    }
+    if offline {
+        cmd.arg("--no-remote");
+    }
+    let output = cmd.output().map_err(|_| Error::ConanNotFound)?;
+    if !output.status.success() {
+        return Err(Error::ConanFail(super::output_tail(&output)));
+    }
+    // the graph is kept for tools that need it without running conan again
+    std::fs::write(dir.join("graph.json"), &output.stdout)?;
+    parse_graph(&String::from_utf8_lossy(&output.stdout), msvc)
+}
+
+/// collects the 'cpp_info' of every host package in a 'conan install --format=json' graph, in link order
+/// (dependents before their dependencies)
+fn parse_graph(json: &str, msvc: bool) -> Result<Dependencies, Error> {
+    let graph: Value = serde_json::from_str(json).map_err(|e| Error::ConanFail(format!("invalid graph output: {e}")))?;
+    let nodes = &graph["graph"]["nodes"];
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    post_order(nodes, "0", &mut visited, &mut order);
+    order.reverse();
+
+    let mut deps = Dependencies::default();
+    let strings = |v: &Value| -> Vec<String> {
+        v.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|s| s.as_str().map(str::to_string))
+            .collect()
+    };
+    // the root node is the generated conanfile itself
+    for id in order.iter().filter(|id| *id != "0") {
+        let node = &nodes[id];
+        let folder = PathBuf::from(node["package_folder"].as_str().unwrap_or_default());
+        let Some(cpp_info) = node["cpp_info"].as_object() else {
+            continue;
+        };
+        let components = cpp_info
+            .get("root")
+            .into_iter()
+            .chain(cpp_info.iter().filter(|(k, _)| *k != "root").map(|(_, v)| v));
+        for comp in components {
+            let dirs = |key: &str| strings(&comp[key]).into_iter().map(|d| folder.join(d)).collect::<Vec<_>>();
+            deps.incdirs.extend(dirs("includedirs"));
+            let libdirs = dirs("libdirs");
+            deps.rpaths.extend(libdirs.iter().cloned());
+            deps.libdirs.extend(libdirs);
+            deps.defines.extend(strings(&comp["defines"]));
+            for lib in strings(&comp["libs"]).into_iter().chain(strings(&comp["system_libs"])) {
+                deps.archives.push(if msvc {
+                    PathBuf::from(lib).with_extension("lib")
+                } else {
+                    PathBuf::from(lib)
+                });
+            }
+        }
+    }
+    Ok(deps)
+}
+
+fn post_order(nodes: &Value, id: &str, visited: &mut HashSet<String>, order: &mut Vec<String>) {
+    if !visited.insert(id.to_string()) || nodes[id]["context"].as_str() == Some("build") {
+        return;
+    }
+    for (dep, edge) in nodes[id]["dependencies"].as_object().into_iter().flatten() {
+        // tool requirements are run, never linked
+        if edge["build"].as_bool() != Some(true) {
+            post_order(nodes, dep, visited, order);
+        }
+    }
+    order.push(id.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a fake 'conan' that records its input and prints the graph of 'spdlog' depending on 'fmt' (and a build tool)
+    #[cfg(unix)]
+    const STUB: &str = r#"#!/bin/sh
+echo "$@" > args.txt
+grep -q broken conanfile.txt && { echo "ERROR: Package 'broken/1.0' not resolved" >&2; exit 1; }
+cat <<EOF
+{"graph": {"nodes": {
+  "0": {"ref": "conanfile", "context": "host", "dependencies": {"1": {"build": false}, "3": {"build": true}}},
+  "1": {"ref": "spdlog/1.14.1", "context": "host", "package_folder": "/conan/p/spdlog",
+        "dependencies": {"2": {"build": false}},
+        "cpp_info": {"root": {"includedirs": ["include"], "libdirs": ["lib"], "libs": ["spdlog"], "defines": ["SPDLOG_FMT_EXTERNAL"], "system_libs": ["pthread"]}}},
+  "2": {"ref": "fmt/10.2.1", "context": "host", "package_folder": "/conan/p/fmt",
+        "dependencies": {},
+        "cpp_info": {"root": {"includedirs": ["/conan/p/fmt/include"], "libdirs": ["lib"], "libs": ["fmt"], "defines": null}}},
+  "3": {"ref": "cmake/3.30.0", "context": "build", "package_folder": "/conan/p/cmake", "dependencies": {},
+        "cpp_info": {"root": {"libs": ["cmake"]}}}
+}}}
+EOF
+"#;
+
+    #[test]
+    fn compiler_versions() {
+        let banner = "Microsoft (R) C/C++ Optimizing Compiler Version 19.40.33811 for x64\nCopyright (C) Microsoft Corporation.";
+        assert_eq!(msvc_version(banner).as_deref(), Some("194"));
+        assert_eq!(msvc_version("Version 19.29.30133 for x86").as_deref(), Some("192"));
+        assert_eq!(msvc_version("usage: cl [ option... ] filename..."), None);
+        assert_eq!(clang_version("clang version 17.0.3\nTarget: x86_64").as_deref(), Some("17"));
+        assert_eq!(clang_version("Ubuntu clang version 18.1.3 (1ubuntu1)").as_deref(), Some("18"));
+        assert_eq!(clang_version("clang-cl: command not found"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn stub_install() {
+        let (dir, stub) = crate::fetch::stub_tool("conan", STUB);
+
+        let options = BTreeMap::from([
+            ("shared".to_string(), ConanOption::Bool(false)),
+            ("fmt/*:header_only".to_string(), ConanOption::Bool(true)),
+        ]);
+        let requires = [ConanRequire {
+            reference: "spdlog/1.14.1".to_string(),
+            options,
+        }];
+        let runtime = Runtime::DynamicRelease;
+        let settings = settings_with(ToolChain::Msvc, &Profile::Release, Lang::Cpp(120), runtime, Some("194"));
+        assert_eq!(
+            settings,
+            [
+                "build_type=Release",
+                "compiler=msvc",
+                "compiler.version=194",
+                "compiler.cppstd=20",
+                "compiler.runtime=dynamic",
+                "compiler.runtime_type=Release"
+            ]
+        );
+
+        let deps = install_with(&stub, &requires, &settings, &dir.join("bin"), true, false).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("bin/conanfile.txt")).unwrap(),
+            "[requires]\nspdlog/1.14.1\n\n[options]\nfmt/*:header_only=True\nspdlog/*:shared=False\n"
+        );
+        let args = std::fs::read_to_string(dir.join("bin/args.txt")).unwrap();
+        assert!(args.starts_with("install . --format=json --build=missing -s build_type=Release -s compiler=msvc"));
+        assert!(args.trim_end().ends_with("--no-remote"));
+
+        assert_eq!(
+            deps.incdirs,
+            [PathBuf::from("/conan/p/spdlog/include"), PathBuf::from("/conan/p/fmt/include")]
+        );
+        assert_eq!(
+            deps.libdirs,
+            [PathBuf::from("/conan/p/spdlog/lib"), PathBuf::from("/conan/p/fmt/lib")]
+        );
+        assert_eq!(
+            deps.archives,
+            [PathBuf::from("spdlog"), PathBuf::from("pthread"), PathBuf::from("fmt")]
+        );
+        assert_eq!(deps.defines, ["SPDLOG_FMT_EXTERNAL"]);
+
+        let broken = [ConanRequire {
+            reference: "broken/1.0".to_string(),
+            options: BTreeMap::new(),
+        }];
+        let err = install_with(&stub, &broken, &settings, &dir.join("bin"), false, false).unwrap_err();
+        assert!(err.to_string().contains("'broken/1.0' not resolved"));
+        assert!(matches!(
+            install_with(&dir.join("missing"), &broken, &settings, &dir.join("bin"), false, false),
+            Err(Error::ConanNotFound)
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
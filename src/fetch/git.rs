@@ -0,0 +1,256 @@
+use crate::error::Error;
+use std::path::Path;
+
+/// The reference a git dependency tracks. At most one of 'rev', 'branch' or 'tag' may be declared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GitRef {
+    DefaultBranch,
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+impl GitRef {
+    pub fn from_fields(name: &str, rev: &Option<String>, branch: &Option<String>, tag: &Option<String>) -> Result<Self, Error> {
+        match (rev, branch, tag) {
+            (None, None, None) => Ok(Self::DefaultBranch),
+            (Some(rev), None, None) => Ok(Self::Rev(rev.clone())),
+            (None, Some(branch), None) => Ok(Self::Branch(branch.clone())),
+            (None, None, Some(tag)) => Ok(Self::Tag(tag.clone())),
+            _ => Err(Error::ConflictingGitRefs(name.to_string())),
+        }
+    }
+
+    /// unique and filesystem safe directory name for a reference
+    pub fn as_directory(&self) -> String {
+        let sanitize = |s: &str| s.replace(['/', '\\', ':'], "_");
+        match self {
+            Self::DefaultBranch => "head".to_string(),
+            Self::Branch(b) => format!("branch-{}", sanitize(b)),
+            Self::Tag(t) => format!("tag-{}", sanitize(t)),
+            Self::Rev(r) => format!("rev-{}", sanitize(r)),
+        }
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|_| Error::GitNotFound)?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(Error::GitFail(
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// clones into a staging directory first, so that a failed clone never leaves a broken cache entry behind
+pub fn clone(url: &str, reference: &GitRef, dest: &Path) -> Result<(), Error> {
+    let staging = dest.with_file_name(format!("{}.partial", dest.file_name().unwrap().to_string_lossy()));
+    let _ = std::fs::remove_dir_all(&staging);
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut args = vec!["clone", "--quiet"];
+    match reference {
+        GitRef::Tag(tag) => args.extend(["--branch", tag, "--depth", "1"]),
+        GitRef::Branch(branch) => args.extend(["--branch", branch]),
+        GitRef::DefaultBranch | GitRef::Rev(_) => (),
+    }
+    args.push(url);
+    let staging_str = staging.to_string_lossy();
+    args.push(&staging_str);
+
+    let result = git(Path::new("."), &args).and_then(|_| match reference {
+        GitRef::Rev(rev) => checkout(&staging, rev),
+        _ => init_submodules(&staging),
+    });
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    std::fs::rename(&staging, dest)?;
+    Ok(())
+}
+
+pub fn head(repo: &Path) -> Result<String, Error> {
+    git(repo, &["rev-parse", "HEAD"])
+}
+
+pub fn has_commit(repo: &Path, rev: &str) -> bool {
+    git(repo, &["cat-file", "-e", &format!("{rev}^{{commit}}")]).is_ok()
+}
+
+pub fn checkout(repo: &Path, rev: &str) -> Result<(), Error> {
+    if head(repo)? == rev {
+        return Ok(());
+    }
+    if !has_commit(repo, rev) {
+        // shallow single-branch clones (tags) may not contain the locked commit
+        let refspec = "+refs/heads/*:refs/remotes/origin/*";
+        if git(repo, &["rev-parse", "--is-shallow-repository"])? == "true" {
+            git(repo, &["fetch", "--quiet", "--unshallow", "--tags", "origin", refspec])?;
+        } else {
+            git(repo, &["fetch", "--quiet", "--tags", "origin", refspec])?;
+        }
+    }
+    git(repo, &["checkout", "--quiet", "--detach", rev])?;
+    init_submodules(repo)
+}
+
+/// fetches the newest state of the tracked reference and checks it out, returns the new commit
+pub fn update(repo: &Path, reference: &GitRef) -> Result<String, Error> {
+    match reference {
+        GitRef::DefaultBranch => {
+            git(repo, &["fetch", "origin"])?;
+            git(repo, &["checkout", "--quiet", "--detach", "origin/HEAD"])?;
+        }
+        GitRef::Branch(branch) => {
+            git(repo, &["fetch", "origin", branch])?;
+            git(repo, &["checkout", "--quiet", "--detach", "FETCH_HEAD"])?;
+        }
+        GitRef::Tag(tag) => {
+            git(repo, &["fetch", "--tags", "origin"])?;
+            git(repo, &["checkout", "--quiet", "--detach", &format!("tags/{tag}")])?;
+        }
+        GitRef::Rev(rev) => {
+            // pinned revisions never move
+            checkout(repo, rev)?;
+        }
+    }
+    init_submodules(repo)?;
+    head(repo)
+}
+
+/// records every change in the working tree, used to publish to git registries. commits are made as the configured
+/// user, or as 'vango' where git has no identity (i.e. on CI), rather than failing
+pub fn commit_all(repo: &Path, message: &str) -> Result<(), Error> {
+    git(repo, &["add", "-A"])?;
+    let mut args = vec!["commit", "--quiet", "-m", message];
+    if git(repo, &["config", "user.email"]).is_err() {
+        args.splice(0..0, ["-c", "user.name=vango", "-c", "user.email=vango@localhost"]);
+    }
+    git(repo, &args)?;
+    Ok(())
+}
+
+/// all files under version control, including those of submodules
+pub fn tracked_files(repo: &Path) -> Result<Vec<std::path::PathBuf>, Error> {
+    Ok(git(repo, &["ls-files", "--recurse-submodules"])?.lines().map(Into::into).collect())
+}
+
+fn init_submodules(repo: &Path) -> Result<(), Error> {
+    if repo.join(".gitmodules").exists() {
+        git(repo, &["submodule", "update", "--init", "--recursive", "--quiet"])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vango-test-git-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn commit(repo: &Path, file: &str, msg: &str) -> String {
+        std::fs::write(repo.join(file), msg).unwrap();
+        git(repo, &["add", "-A"]).unwrap();
+        git(repo, &["-c", "user.name=vango", "-c", "user.email=vango@localhost", "commit", "--quiet", "-m", msg]).unwrap();
+        head(repo).unwrap()
+    }
+
+    fn origin(dir: &Path) -> (String, String, String) {
+        let repo = dir.join("origin");
+        std::fs::create_dir_all(&repo).unwrap();
+        git(&repo, &["init", "--quiet", "--initial-branch=main"]).unwrap();
+        let first = commit(&repo, "a.txt", "first");
+        git(&repo, &["tag", "v1.0.0"]).unwrap();
+        let second = commit(&repo, "b.txt", "second");
+        (format!("file://{}", repo.display()), first, second)
+    }
+
+    #[test]
+    fn git_ref_fields() {
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(GitRef::from_fields("a", &None, &None, &None).unwrap(), GitRef::DefaultBranch);
+        assert_eq!(GitRef::from_fields("a", &some("abc"), &None, &None).unwrap(), GitRef::Rev("abc".into()));
+        assert_eq!(GitRef::from_fields("a", &None, &some("dev"), &None).unwrap(), GitRef::Branch("dev".into()));
+        assert_eq!(GitRef::from_fields("a", &None, &None, &some("v1")).unwrap(), GitRef::Tag("v1".into()));
+        assert!(GitRef::from_fields("a", &some("abc"), &None, &some("v1")).is_err());
+        assert_eq!(GitRef::Branch("feature/x".into()).as_directory(), "branch-feature_x");
+    }
+
+    #[test]
+    fn clone_references() {
+        let dir = scratch_dir("clone");
+        let (url, first, second) = origin(&dir);
+
+        let head_dir = dir.join("head");
+        clone(&url, &GitRef::DefaultBranch, &head_dir).unwrap();
+        assert_eq!(head(&head_dir).unwrap(), second);
+
+        let tag_dir = dir.join("tag");
+        clone(&url, &GitRef::Tag("v1.0.0".into()), &tag_dir).unwrap();
+        assert_eq!(head(&tag_dir).unwrap(), first);
+
+        let rev_dir = dir.join("rev");
+        clone(&url, &GitRef::Rev(first.clone()), &rev_dir).unwrap();
+        assert_eq!(head(&rev_dir).unwrap(), first);
+
+        let branch_dir = dir.join("branch");
+        clone(&url, &GitRef::Branch("main".into()), &branch_dir).unwrap();
+        assert_eq!(head(&branch_dir).unwrap(), second);
+
+        // locked commits older than a shallow clone are still reachable
+        checkout(&tag_dir, &second).unwrap();
+        assert_eq!(head(&tag_dir).unwrap(), second);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn clone_failure() {
+        let dir = scratch_dir("fail");
+        let dest = dir.join("missing");
+        let url = format!("file://{}", dir.join("does-not-exist").display());
+        assert!(matches!(clone(&url, &GitRef::DefaultBranch, &dest), Err(Error::GitFail(..))));
+        assert!(!dest.exists());
+        assert!(!dir.join("missing.partial").exists());
+
+        let (url, _, _) = origin(&dir);
+        assert!(clone(&url, &GitRef::Tag("v9.9.9".into()), &dest).is_err());
+        assert!(!dest.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn update_moves_to_newest() {
+        let dir = scratch_dir("update");
+        let (url, first, second) = origin(&dir);
+
+        let dest = dir.join("head");
+        clone(&url, &GitRef::DefaultBranch, &dest).unwrap();
+        checkout(&dest, &first).unwrap();
+        assert_eq!(update(&dest, &GitRef::DefaultBranch).unwrap(), second);
+
+        let third = commit(&dir.join("origin"), "c.txt", "third");
+        assert_eq!(update(&dest, &GitRef::Branch("main".into())).unwrap(), third);
+        assert_eq!(update(&dest, &GitRef::Rev(first.clone())).unwrap(), first);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,182 @@
+use super::git::GitRef;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const LOCKFILE: &str = "Vango.lock";
+
+const LOCKFILE_HEADER: &str = "# This file is automatically generated by vango.\n# It is not intended for manual editing.\n";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockFile {
+    version: u32,
+    #[serde(default, rename = "package", skip_serializing_if = "Vec::is_empty")]
+    packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+impl Default for LockFile {
+    fn default() -> Self {
+        Self {
+            version: 1,
+            packages: Vec::new(),
+        }
+    }
+}
+
+impl LockFile {
+    pub fn load(dir: &Path) -> Result<Self, Error> {
+        match std::fs::read_to_string(dir.join(LOCKFILE)) {
+            Ok(file) => Ok(toml::from_str(&file)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::FileSystem(e)),
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), Error> {
+        let body = toml::to_string(self).expect("lockfile is always serializable");
+        std::fs::write(dir.join(LOCKFILE), format!("{LOCKFILE_HEADER}\n{body}"))?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str, source: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| p.name == name && p.source == source)
+    }
+
+    pub fn get_mut(&mut self, name: &str, source: &str) -> Option<&mut LockedPackage> {
+        self.packages.iter_mut().find(|p| p.name == name && p.source == source)
+    }
+
+    pub fn insert(&mut self, package: LockedPackage) {
+        if let Some(existing) = self.get_mut(&package.name, &package.source) {
+            *existing = package;
+        } else {
+            // keep entries sorted for stable diffs
+            let i = self
+                .packages
+                .partition_point(|p| (&p.name, &p.source) < (&package.name, &package.source));
+            self.packages.insert(i, package);
+        }
+    }
+
+    pub fn packages(&self) -> &[LockedPackage] {
+        &self.packages
+    }
+
+    pub fn packages_mut(&mut self) -> &mut [LockedPackage] {
+        &mut self.packages
+    }
+
+    pub fn retain<P: FnMut(&LockedPackage) -> bool>(&mut self, p: P) {
+        self.packages.retain(p);
+    }
+}
+
+pub fn git_source(url: &str, reference: &GitRef) -> String {
+    match reference {
+        GitRef::DefaultBranch => format!("git+{url}"),
+        GitRef::Branch(branch) => format!("git+{url}?branch={branch}"),
+        GitRef::Tag(tag) => format!("git+{url}?tag={tag}"),
+        GitRef::Rev(rev) => format!("git+{url}?rev={rev}"),
+    }
+}
+
+pub fn registry_source(index: &str) -> String {
+    format!("registry+{index}")
+}
+
+pub fn parse_git_source(source: &str) -> Option<(&str, GitRef)> {
+    let source = source.strip_prefix("git+")?;
+    let Some((url, query)) = source.split_once('?') else {
+        return Some((source, GitRef::DefaultBranch));
+    };
+    match query.split_once('=')? {
+        ("branch", branch) => Some((url, GitRef::Branch(branch.to_string()))),
+        ("tag", tag) => Some((url, GitRef::Tag(tag.to_string()))),
+        ("rev", rev) => Some((url, GitRef::Rev(rev.to_string()))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn git_source_roundtrip() {
+        let a = git_source("https://github.com/EmVance1/ShimmyNav.git", &GitRef::DefaultBranch);
+        let b = git_source("https://github.com/SFML/SFML.git", &GitRef::Tag("3.0.1".into()));
+        let c = git_source("https://github.com/SFML/SFML.git", &GitRef::Branch("master".into()));
+        let d = git_source("https://github.com/SFML/SFML.git", &GitRef::Rev("0123abcd".into()));
+        assert_eq!(parse_git_source(&a), Some(("https://github.com/EmVance1/ShimmyNav.git", GitRef::DefaultBranch)));
+        assert_eq!(parse_git_source(&b), Some(("https://github.com/SFML/SFML.git", GitRef::Tag("3.0.1".into()))));
+        assert_eq!(parse_git_source(&c), Some(("https://github.com/SFML/SFML.git", GitRef::Branch("master".into()))));
+        assert_eq!(parse_git_source(&d), Some(("https://github.com/SFML/SFML.git", GitRef::Rev("0123abcd".into()))));
+        assert_eq!(parse_git_source("../engine"), None);
+    }
+
+    #[test]
+    fn lockfile_roundtrip() {
+        let mut lock = LockFile::default();
+        lock.insert(LockedPackage {
+            name: "SFML".to_string(),
+            version: "3.0.1".to_string(),
+            source: git_source("https://github.com/SFML/SFML.git", &GitRef::Tag("3.0.1".into())),
+            rev: Some("0123456789abcdef".to_string()),
+            checksum: None,
+        });
+        lock.insert(LockedPackage {
+            name: "LuaJIT".to_string(),
+            version: "2.1.0".to_string(),
+            source: git_source("https://github.com/LuaJIT/LuaJIT.git", &GitRef::DefaultBranch),
+            rev: Some("fedcba9876543210".to_string()),
+            checksum: None,
+        });
+        lock.insert(LockedPackage {
+            name: "fmt".to_string(),
+            version: "10.2.1".to_string(),
+            source: registry_source("/srv/vango-index"),
+            rev: None,
+            checksum: Some("9f86d081884c7d65".to_string()),
+        });
+
+        let text = toml::to_string(&lock).unwrap();
+        let back: LockFile = toml::from_str(&text).unwrap();
+        assert_eq!(back, lock);
+        assert_eq!(back.packages()[0].name, "LuaJIT");
+        assert_eq!(back.packages()[1].name, "SFML");
+        assert_eq!(back.packages()[2].name, "fmt");
+    }
+
+    #[test]
+    fn lockfile_insert_replaces() {
+        let mut lock = LockFile::default();
+        let source = git_source("https://github.com/SFML/SFML.git", &GitRef::DefaultBranch);
+        lock.insert(LockedPackage {
+            name: "SFML".to_string(),
+            version: "3.0.0".to_string(),
+            source: source.clone(),
+            rev: Some("aaaa".to_string()),
+            checksum: None,
+        });
+        lock.insert(LockedPackage {
+            name: "SFML".to_string(),
+            version: "3.0.1".to_string(),
+            source: source.clone(),
+            rev: Some("bbbb".to_string()),
+            checksum: None,
+        });
+        assert_eq!(lock.packages().len(), 1);
+        assert_eq!(lock.get("SFML", &source).unwrap().rev.as_deref(), Some("bbbb"));
+    }
+}
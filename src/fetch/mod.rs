@@ -0,0 +1,841 @@
+pub mod archive;
+pub mod conan;
+pub mod git;
+pub mod lock;
+pub mod pkgconfig;
+pub mod recipe;
+pub mod registry;
+pub mod vcpkg;
+
+use crate::{
+    config::{BuildFile, Dependency, GlobalConfig, LibFile, Patches, Profile, Runtime, ToolChain, VangoFile, VcpkgConfig, Version},
+    error::Error,
+    input::BuildSwitches,
+    log_info_ln, log_warn_ln,
+};
+use git::GitRef;
+use lock::{LockFile, LockedPackage};
+use vcpkg::VcpkgDependency;
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    process::Output,
+};
+
+pub fn source_files(sdir: &Path, ext: &str) -> Result<Vec<PathBuf>, Error> {
+    let mut res = Vec::new();
+
+    for e in std::fs::read_dir(sdir)? {
+        let e = e?;
+        if e.path().is_dir() {
+            res.extend(source_files(&e.path(), ext)?);
+        } else if e.path().is_file() && e.path().extension().unwrap_or(OsStr::new("")) == ext {
+            res.push(e.path());
+        }
+    }
+
+    Ok(res)
+}
+
+pub fn pull_git_repo(url: &str, reference: &GitRef, install_loc: &Path) -> Result<(), Error> {
+    log_info_ln!("{:-<80}", format!("cloning project dependency to: {} ", install_loc.display()));
+    git::clone(url, reference, install_loc)
+}
+
+/// packages are cached per (url, reference), as neither repo names nor tags are unique on their own
+pub fn git_cache_path(url: &str, reference: &GitRef) -> PathBuf {
+    std::env::home_dir()
+        .unwrap()
+        .join(".vango/packages")
+        .join(git_cache_key(url))
+        .join(reference.as_directory())
+}
+
+pub(crate) fn git_cache_key(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+    let stem = Path::new(url).file_stem().unwrap_or(OsStr::new("package")).to_string_lossy();
+    // FNV-1a, stable across toolchains and platforms unlike std's DefaultHasher
+    let hash = url
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3));
+    format!("{stem}-{hash:016x}")
+}
+
+/// clones a git dependency if it is not yet cached, and pins it to the commit recorded in the lockfile
+pub fn resolve_git(name: &str, url: &str, reference: &GitRef, resolver: &mut Resolver) -> Result<(PathBuf, String), Error> {
+    let source = lock::git_source(url, reference);
+    if let Some(vendor) = &resolver.vendor {
+        let path = vendor.join(git_vendor_path(url, reference));
+        let rev = std::fs::read_to_string(path.join(VENDOR_REV_FILE)).map_err(|_| Error::NotVendored(name.to_string()))?;
+        resolver.lock_vendored(name, &source, rev.trim())?;
+        return Ok((path, source));
+    }
+
+    let path = git_cache_path(url, reference);
+    if !std::fs::exists(&path)? {
+        if resolver.offline {
+            return Err(Error::OfflineFetch(name.to_string()));
+        }
+        pull_git_repo(url, reference, &path)?;
+    }
+    resolver.lock_git(name, &source, &path)?;
+    Ok((path, source))
+}
+
+/// location of a git dependency relative to the vendor directory, mirrors the package cache layout
+pub fn git_vendor_path(url: &str, reference: &GitRef) -> PathBuf {
+    PathBuf::from(git_cache_key(url)).join(reference.as_directory())
+}
+
+/// vendored sources are plain copies without history, the commit they were taken from is recorded here
+pub const VENDOR_REV_FILE: &str = ".vango-rev";
+
+pub fn copy_dir_all(src: &Path, dst: &Path) -> Result<(), Error> {
+    std::fs::create_dir_all(dst)?;
+    for e in std::fs::read_dir(src)? {
+        let e = e?;
+        if e.path().is_dir() {
+            copy_dir_all(&e.path(), &dst.join(e.file_name()))?;
+        } else {
+            std::fs::copy(e.path(), dst.join(e.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// the last lines of a failed tool's stdout and stderr, usually enough to see what went wrong
+pub fn output_tail(output: &Output) -> String {
+    let log = String::from_utf8_lossy(&output.stdout).to_string() + &String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<_> = log.lines().collect();
+    lines[lines.len().saturating_sub(20)..].join("\n")
+}
+
+/// Dependency resolution state shared by the root project and all of its recursive builds.
+/// Owns the root lockfile, which pins every git dependency in the graph to a commit.
+#[derive(Debug)]
+pub struct Resolver {
+    root: PathBuf,
+    lock: LockFile,
+    locked: bool,
+    offline: bool,
+    vendor: Option<PathBuf>,
+    config: GlobalConfig,
+    /// '[patch]' of the root project merged with that of the config, the config takes precedence
+    patches: Patches,
+    /// patches that replaced at least one dependency, as (source, name)
+    applied: HashSet<(String, String)>,
+    /// source packages currently being built, from the root down, to detect cycles
+    stack: Vec<(String, PathBuf)>,
+    /// source packages already built by this invocation, keyed by (root, profile, toolchain)
+    built: HashMap<(PathBuf, String, ToolChain), (LibFile, Dependencies)>,
+    /// every package in the graph by name, to detect diamonds resolving to different versions
+    packages: HashMap<String, (PathBuf, Version)>,
+    /// the root's dev-dependencies are not part of this invocation, so unvisited lock entries may still be in use
+    partial: bool,
+    /// git registries whose index was already fetched by this invocation
+    refreshed: HashSet<String>,
+    touched: HashSet<(String, String)>,
+    changed: bool,
+}
+
+impl Resolver {
+    pub fn new(build: &BuildFile, locked: bool, offline: bool) -> Result<Self, Error> {
+        let root = std::env::current_dir()?;
+        let canonical = std::fs::canonicalize(&root)?;
+        let config = GlobalConfig::load(&root)?;
+        let mut patches = build.patch.clone();
+        patches.rebase(&root);
+        patches.merge(config.patch.clone());
+        Ok(Self {
+            lock: LockFile::load(&root)?,
+            root,
+            locked,
+            offline: offline || config.net.offline,
+            vendor: config.source.vendor.clone(),
+            config,
+            patches,
+            applied: HashSet::new(),
+            stack: vec![(build.name.clone(), canonical)],
+            built: HashMap::new(),
+            packages: HashMap::new(),
+            partial: !build.dev_dependencies.is_empty(),
+            refreshed: HashSet::new(),
+            touched: HashSet::new(),
+            changed: false,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// resolve from the package cache even if a vendor directory is configured (used to (re)create it)
+    pub fn ignore_vendor(&mut self) {
+        self.vendor = None;
+    }
+
+    /// the replacement of a dependency if it is patched, patches apply to every occurrence in the graph
+    pub fn patch(&mut self, name: &str, dep: &Dependency) -> Option<Dependency> {
+        let source = match dep {
+            Dependency::Git { git, .. } => git.clone(),
+            Dependency::Archive { archive, .. } => archive.clone(),
+            Dependency::Registry { registry, .. } => self.config.registry(registry.as_deref()).ok()?.0.to_string(),
+            _ => return None,
+        };
+        let (source, patched) = self.patches.get(&source, name)?;
+        self.applied.insert((source.to_string(), name.to_string()));
+        Some(patched.clone())
+    }
+
+    /// enters a source package, unless it is already being built further up the graph
+    fn enter(&mut self, name: &str, root: &Path) -> Result<(), Error> {
+        if let Some(pos) = self.stack.iter().position(|(_, r)| r == root) {
+            let chain: Vec<_> = self.stack[pos..].iter().map(|(n, _)| n.as_str()).chain([name]).collect();
+            return Err(Error::DependencyCycle(chain.join(" -> ")));
+        }
+        self.stack.push((name.to_string(), root.to_path_buf()));
+        Ok(())
+    }
+
+    fn leave(&mut self) {
+        self.stack.pop();
+    }
+
+    /// a package may appear at several places of the graph, but only ever as one version
+    fn register(&mut self, name: &str, version: Version, root: &Path) -> Result<(), Error> {
+        match self.packages.get(name) {
+            Some((other, _)) if other == root => Ok(()),
+            Some((other, v)) if *v != version => Err(Error::VersionConflict(
+                name.to_string(),
+                *v,
+                other.clone(),
+                version,
+                root.to_path_buf(),
+            )),
+            Some((other, _)) => {
+                log_warn_ln!(
+                    "package '{name}' v{version} is part of the dependency graph twice: {} and {}",
+                    other.display(),
+                    root.display()
+                );
+                Ok(())
+            }
+            None => {
+                self.packages.insert(name.to_string(), (root.to_path_buf(), version));
+                Ok(())
+            }
+        }
+    }
+
+    fn record(&mut self, name: &str, source: &str, rev: String) -> Result<(), Error> {
+        if self.locked {
+            return Err(Error::LockOutdated(name.to_string()));
+        }
+        self.lock.insert(LockedPackage {
+            name: name.to_string(),
+            version: String::new(),
+            source: source.to_string(),
+            rev: Some(rev),
+            checksum: None,
+        });
+        self.changed = true;
+        Ok(())
+    }
+
+    /// the version a registry dependency is locked to, if it is locked at all
+    pub fn locked_version(&self, name: &str, source: &str) -> Option<Version> {
+        self.lock.get(name, source).and_then(|p| p.version.parse().ok())
+    }
+
+    fn lock_registry(&mut self, name: &str, source: &str, version: Version, checksum: &str) -> Result<(), Error> {
+        self.touched.insert((name.to_string(), source.to_string()));
+        let package = LockedPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+            source: source.to_string(),
+            rev: None,
+            checksum: Some(checksum.to_string()),
+        };
+        if self.lock.get(name, source) != Some(&package) {
+            if self.locked {
+                return Err(Error::LockOutdated(name.to_string()));
+            }
+            self.lock.insert(package);
+            self.changed = true;
+        }
+        Ok(())
+    }
+
+    /// checks out the locked commit of a cloned git dependency, or records its current commit if unlocked
+    fn lock_git(&mut self, name: &str, source: &str, repo: &Path) -> Result<(), Error> {
+        self.touched.insert((name.to_string(), source.to_string()));
+        if let Some(rev) = self.lock.get(name, source).and_then(|p| p.rev.clone()) {
+            if self.offline && !git::has_commit(repo, &rev) {
+                return Err(Error::OfflineFetch(name.to_string()));
+            }
+            git::checkout(repo, &rev)
+        } else {
+            self.record(name, source, git::head(repo)?)
+        }
+    }
+
+    /// vendored copies can not be checked out, they must already match the lockfile
+    fn lock_vendored(&mut self, name: &str, source: &str, rev: &str) -> Result<(), Error> {
+        self.touched.insert((name.to_string(), source.to_string()));
+        match self.lock.get(name, source).and_then(|p| p.rev.as_deref()) {
+            Some(locked) if locked == rev => Ok(()),
+            Some(_) => Err(Error::VendorOutdated(name.to_string())),
+            None => self.record(name, source, rev.to_string()),
+        }
+    }
+
+    fn lock_version(&mut self, name: &str, source: &str, version: Version) -> Result<(), Error> {
+        let version = version.to_string();
+        if let Some(pkg) = self.lock.get_mut(name, source)
+            && pkg.version != version
+        {
+            if self.locked {
+                return Err(Error::LockOutdated(name.to_string()));
+            }
+            pkg.version = version;
+            self.changed = true;
+        }
+        Ok(())
+    }
+
+    /// drops lock entries that are no longer part of the graph and writes the lockfile if anything changed
+    pub fn finish(mut self) -> Result<(), Error> {
+        for (source, name, _) in self.patches.iter() {
+            if !self.applied.contains(&(source.to_string(), name.to_string())) {
+                log_warn_ln!("patch for '{name}' from '{source}' was not used in the dependency graph");
+            }
+        }
+        if !self.partial
+            && let Some(stale) = self
+                .lock
+                .packages()
+                .iter()
+                .find(|p| !self.touched.contains(&(p.name.clone(), p.source.clone())))
+        {
+            if self.locked {
+                return Err(Error::LockOutdated(stale.name.clone()));
+            }
+            self.lock.retain(|p| self.touched.contains(&(p.name.clone(), p.source.clone())));
+            self.changed = true;
+        }
+        if self.changed {
+            self.lock.save(&self.root)?;
+        }
+        Ok(())
+    }
+}
+
+/// installs the vcpkg packages of a project and links them, the libraries of packages without explicit
+/// 'targets' are discovered from the installed tree
+fn pull_vcpkg(
+    packages: Vec<VcpkgDependency>,
+    discover: &[&str],
+    config: &VcpkgConfig,
+    switches: &BuildSwitches,
+    deps: &mut Dependencies,
+    resolver: &Resolver,
+) -> Result<(), Error> {
+    if packages.is_empty() {
+        return Ok(());
+    }
+    let names = || packages.iter().map(|p| p.name()).collect::<Vec<_>>().join(", ");
+    let triplet = &config.triplet;
+
+    let installed = if let Some(vendor) = &resolver.vendor {
+        let installed = vendor.join("vcpkg_installed");
+        if !installed.join(triplet).exists() {
+            return Err(Error::NotVendored(format!("vcpkg: {}", names())));
+        }
+        installed
+    } else if resolver.offline {
+        let installed = PathBuf::from("bin/vcpkg_installed");
+        if !installed.join(triplet).exists() {
+            return Err(Error::OfflineFetch(format!("vcpkg: {}", names())));
+        }
+        installed
+    } else {
+        vcpkg::install(&packages, config)?
+    };
+
+    // vcpkg installs debug binaries separately, when the port provides them
+    let debug = switches.profile.is_debug() && installed.join(triplet).join("debug").join("lib").exists();
+    let libdir = if debug {
+        installed.join(triplet).join("debug").join("lib")
+    } else {
+        installed.join(triplet).join("lib")
+    };
+    deps.incdirs.push(installed.join(triplet).join("include"));
+    deps.libdirs.push(libdir.clone());
+    deps.rpaths.push(libdir);
+    if !discover.is_empty() {
+        deps.archives.extend(vcpkg::libraries(&installed, triplet, discover, debug, switches.toolchain.is_msvc())?);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Dependencies {
+    pub incdirs: Vec<PathBuf>,
+    pub libdirs: Vec<PathBuf>,
+    pub rpaths: Vec<PathBuf>,
+    pub archives: Vec<PathBuf>,
+    pub relink: Vec<PathBuf>,
+    /// shared libraries built or shipped by vango dependencies, loaded at runtime by every executable using them
+    pub shared_libs: Vec<PathBuf>,
+    pub defines: Vec<String>,
+    /// flags reported by pkg-config that are not include directories, definitions or libraries (i.e. '-pthread')
+    pub compiler_options: Vec<String>,
+    pub linker_options: Vec<String>,
+    /// pkg-config modules linked by the project, the 'Requires.private' of its generated '.pc' file
+    pub requires: Vec<String>,
+    /// public requirements of dependencies, passed on to the consumers of the project being built
+    pub public_incdirs: Vec<PathBuf>,
+    pub public_defines: Vec<String>,
+}
+
+impl Dependencies {
+    /// a library shared by several dependencies is linked once, after all of them (single pass linkers
+    /// only resolve symbols from archives that follow their users), everything else is kept in first-seen order
+    fn dedup(&mut self) {
+        fn first_seen<T: Clone + Eq + std::hash::Hash>(items: &mut Vec<T>) {
+            let mut seen = HashSet::new();
+            items.retain(|i| seen.insert(i.clone()));
+        }
+        first_seen(&mut self.incdirs);
+        first_seen(&mut self.defines);
+        first_seen(&mut self.public_incdirs);
+        first_seen(&mut self.public_defines);
+        first_seen(&mut self.libdirs);
+        first_seen(&mut self.rpaths);
+        first_seen(&mut self.relink);
+        first_seen(&mut self.shared_libs);
+        first_seen(&mut self.requires);
+        self.archives.reverse();
+        first_seen(&mut self.archives);
+        self.archives.reverse();
+    }
+}
+
+/// where a dependency lives once fetched
+pub enum Located {
+    /// a package root, with a manifest unless built by a recipe, 'source' is its lockfile source if locked by version
+    Root {
+        path: PathBuf,
+        source: Option<String>,
+    },
+    /// a header-only dependency, no manifest
+    Headers(PathBuf),
+    System(PathBuf),
+    /// a module found by pkg-config at build time
+    PkgConfig {
+        module: String,
+        version: Option<String>,
+    },
+    Vcpkg,
+    /// a conan reference, only resolved by 'conan install' once all of a project's dependencies are known
+    Conan(String),
+}
+
+/// fetches a dependency if necessary (git, registry, archive), and returns where it lives
+pub fn locate(name: &str, dep: &Dependency, resolver: &mut Resolver) -> Result<Located, Error> {
+    Ok(match dep {
+        Dependency::Git {
+            git,
+            tag,
+            branch,
+            rev,
+            path,
+            headers,
+            ..
+        } => {
+            let reference = GitRef::from_fields(name, rev, branch, tag)?;
+            let (repo, source) = resolve_git(name, git, &reference, resolver)?;
+            let root = if let Some(path) = path { repo.join(path) } else { repo };
+            if let Some(headers) = headers {
+                Located::Headers(root.join(headers))
+            } else {
+                Located::Root {
+                    path: root,
+                    source: Some(source),
+                }
+            }
+        }
+        Dependency::Registry { version, registry, .. } => {
+            let (path, source) = registry::resolve(name, version, registry.as_deref(), resolver)?;
+            Located::Root {
+                path,
+                source: Some(source),
+            }
+        }
+        Dependency::Archive {
+            archive,
+            sha256,
+            strip_prefix,
+            manifest,
+            headers,
+            ..
+        } => {
+            let root = archive::resolve(name, archive, sha256, *strip_prefix, manifest.as_deref(), resolver)?;
+            if let Some(headers) = headers {
+                Located::Headers(root.join(headers))
+            } else {
+                Located::Root { path: root, source: None }
+            }
+        }
+        Dependency::Package { src, .. } if src == "vcpkg" => Located::Vcpkg,
+        Dependency::Package { src, .. } => Located::Root {
+            path: src.clone(),
+            source: None,
+        },
+        Dependency::Headers { headers, .. } => Located::Headers(headers.clone()),
+        Dependency::Conan { conan, .. } => Located::Conan(conan.clone()),
+        Dependency::System { system } => Located::System(system.clone()),
+        Dependency::PkgConfig { pkg_config, version } => Located::PkgConfig {
+            module: pkg_config.clone(),
+            version: version.clone(),
+        },
+    })
+}
+
+pub fn libraries(info: &BuildFile, profile: &Profile, switches: &BuildSwitches, resolver: &mut Resolver) -> Result<Dependencies, Error> {
+    collect_libraries(info, false, profile, switches, resolver)
+}
+
+/// the dependencies of the root project's tests, i.e. its dependencies followed by its dev-dependencies
+pub fn test_libraries(
+    info: &BuildFile,
+    profile: &Profile,
+    switches: &BuildSwitches,
+    resolver: &mut Resolver,
+) -> Result<Dependencies, Error> {
+    resolver.partial = false;
+    collect_libraries(info, true, profile, switches, resolver)
+}
+
+fn collect_libraries(
+    info: &BuildFile,
+    dev: bool,
+    profile: &Profile,
+    switches: &BuildSwitches,
+    resolver: &mut Resolver,
+) -> Result<Dependencies, Error> {
+    let mut deps = Dependencies::default();
+    // static runtimes link everything statically, including what pkg-config modules only need privately
+    let settings = info.get(&switches.profile)?.settings;
+    let runtime = settings.runtime;
+    let static_link = matches!(runtime, Runtime::StaticDebug | Runtime::StaticRelease);
+
+    // recursive builds only forward base (inherited) profile, custom profiles ignored
+    let switches = if let Profile::Custom(..) = switches.profile {
+        BuildSwitches {
+            profile: profile.clone(),
+            ..switches.clone()
+        }
+    } else {
+        switches.clone()
+    };
+
+    let mut vcpkg = Vec::new();
+    let mut discover = Vec::new();
+    let mut conan = Vec::new();
+
+    let dev_dependencies = if dev { info.dev_dependencies.as_slice() } else { &[] };
+    for lib in info.dependencies.iter().chain(dev_dependencies) {
+        let patched = resolver.patch(&lib.0, &lib.1);
+        let dep = patched.as_ref().unwrap_or(&lib.1);
+        // get path to library root, pull repo if necessary
+        let (path, source) = match locate(&lib.0, dep, resolver)? {
+            Located::Root { path, source } => (path, source),
+            Located::Headers(headers) => {
+                deps.incdirs.push(headers);
+                continue;
+            }
+            Located::System(system) => {
+                if switches.toolchain.is_msvc() {
+                    deps.archives.push(system.with_extension("lib"));
+                } else {
+                    deps.archives.push(system);
+                }
+                continue;
+            }
+            Located::PkgConfig { module, version } => {
+                deps.requires.push(pkgconfig::spec(&module, version.as_deref()));
+                let module = pkgconfig::probe(&module, version.as_deref(), static_link, switches.toolchain.is_msvc())?;
+                deps.incdirs.extend(module.incdirs);
+                deps.defines.extend(module.defines);
+                deps.libdirs.extend(module.libdirs);
+                deps.rpaths.extend(module.rpaths);
+                deps.archives.extend(module.archives);
+                deps.compiler_options.extend(module.compiler_options);
+                deps.linker_options.extend(module.linker_options);
+                continue;
+            }
+            Located::Vcpkg => {
+                if let Dependency::Package {
+                    targets,
+                    features,
+                    version,
+                    ..
+                } = dep
+                {
+                    vcpkg.push(VcpkgDependency::new(&lib.0, features, version.as_deref()));
+                    if targets.is_empty() {
+                        discover.push(lib.0.as_str());
+                    }
+                    for tar in targets {
+                        if switches.toolchain.is_msvc() {
+                            deps.archives.push(tar.with_extension("lib"));
+                        } else {
+                            deps.archives.push(tar.clone());
+                        }
+                    }
+                }
+                continue;
+            }
+            Located::Conan(reference) => {
+                if let Dependency::Conan { options, .. } = dep {
+                    conan.push(conan::ConanRequire {
+                        reference,
+                        options: options.clone(),
+                    });
+                }
+                continue;
+            }
+        };
+
+        if !std::fs::exists(&path).unwrap() {
+            return Err(Error::DirectoryNotFound(path));
+        }
+
+        let mut srcpkg = false;
+        let mut transitive = Dependencies::default();
+        let root = std::fs::canonicalize(&path)?;
+        let mut library = if let Some(recipe) = dep.recipe() {
+            recipe::build(&lib.0, &root, recipe, &switches, &settings, info.lang)?
+        } else {
+            let save = std::env::current_dir().unwrap();
+            std::env::set_current_dir(&path).unwrap();
+            let library = match VangoFile::from_str(&crate::read_manifest()?)? {
+                VangoFile::Build(build) => {
+                    // could use .validate(), but prefer checking *before* build to save user time
+                    if build.interface > info.lang {
+                        return Err(Error::IncompatibleCppStd(build.name, build.interface, info.name.clone(), info.lang));
+                    }
+                    srcpkg = true;
+                    resolver.register(&build.name, build.version, &root)?;
+                    // diamonds share one build of the package they have in common
+                    let key = (root.clone(), switches.profile.to_string(), switches.toolchain);
+                    if let Some((library, interface)) = resolver.built.get(&key) {
+                        transitive = interface.clone();
+                        library.clone()
+                    } else {
+                        resolver.enter(&build.name, &root)?;
+                        transitive = crate::action::build(&build, &switches, resolver, true)?;
+                        resolver.leave();
+                        let library = LibFile::from_build(build, switches.toolchain)?;
+                        resolver.built.insert(key, (library.clone(), transitive.clone()));
+                        library
+                    }
+                }
+                VangoFile::Lib(lib) => {
+                    resolver.register(&lib.name, lib.version, &root)?;
+                    lib.validate(&info.name, info.lang)?
+                }
+            };
+            std::env::set_current_dir(&save).unwrap();
+            if let Some(source) = &source {
+                resolver.lock_version(&lib.0, source, library.version)?;
+            }
+            library
+        };
+
+        // collect all dependency artefacts (includes, definitions, libraries, libdirs) into SOA
+        let profile = library.take(&switches.profile)?;
+        deps.incdirs.push(path.join(profile.include));
+        let libdir = path.join(&profile.libdir);
+        deps.shared_libs.extend(shared_libraries(&libdir, &profile.binaries)?);
+        deps.libdirs.push(libdir);
+        if switches.toolchain.is_msvc() {
+            for l in profile.binaries {
+                if srcpkg {
+                    deps.relink.push(path.join(&profile.libdir).join(&l).with_extension("lib"));
+                }
+                deps.archives.push(l.with_extension("lib"));
+            }
+        } else {
+            for l in profile.binaries {
+                if srcpkg {
+                    deps.relink
+                        .push(path.join(&profile.libdir).join(format!("lib{}", l.display())).with_extension("a"));
+                }
+                deps.archives.push(l);
+            }
+        }
+
+        // public requirements pass through every consumer, paths are relative to the library root
+        let public_incdirs: Vec<_> = transitive.incdirs.into_iter().map(|i| path.join(i)).collect();
+        deps.incdirs.extend(public_incdirs.iter().cloned());
+        deps.public_incdirs.extend(public_incdirs);
+        deps.defines.extend(transitive.defines.iter().cloned());
+        deps.public_defines.extend(transitive.defines);
+
+        // what a static library links against follows it
+        deps.libdirs.extend(transitive.libdirs.into_iter().map(|l| path.join(l)));
+        deps.rpaths.extend(transitive.rpaths.into_iter().map(|r| path.join(r)));
+        deps.relink.extend(transitive.relink.into_iter().map(|r| path.join(r)));
+        deps.shared_libs.extend(transitive.shared_libs.into_iter().map(|s| path.join(s)));
+        deps.archives.extend(transitive.archives);
+        deps.linker_options.extend(transitive.linker_options);
+        deps.requires.extend(transitive.requires);
+
+        // no vango generated definitions are propagated - all such defs are tailored to the project being built
+        deps.defines
+            .extend(profile.defines.into_iter().filter(|d| !d.starts_with("VANGO_")));
+    }
+
+    pull_vcpkg(vcpkg, &discover, &info.vcpkg, &switches, &mut deps, resolver)?;
+    if !conan.is_empty() {
+        let settings = conan::settings(switches.toolchain, &switches.profile, info.lang, runtime)?;
+        let dir = PathBuf::from("bin")
+            .join("conan")
+            .join(switches.toolchain.as_directory())
+            .join(switches.profile.to_string());
+        // conan has its own cache, vendored and offline builds only use what is already in it
+        let offline = resolver.offline || resolver.vendor.is_some();
+        let packages = conan::install(&conan, &settings, &dir, offline, switches.toolchain.is_msvc())?;
+        deps.incdirs.extend(packages.incdirs);
+        deps.libdirs.extend(packages.libdirs);
+        deps.rpaths.extend(packages.rpaths);
+        deps.archives.extend(packages.archives);
+        deps.defines.extend(packages.defines);
+    }
+    deps.dedup();
+
+    Ok(deps)
+}
+
+/// the shared libraries of a dependency's binaries in its libdir, with their versioned files and links
+/// ('libname.so', 'libname.so.1' and 'libname.so.1.2.3', 'libname.1.dylib' or 'name.dll')
+fn shared_libraries(libdir: &Path, binaries: &[PathBuf]) -> Result<Vec<PathBuf>, Error> {
+    let mut shared = Vec::new();
+    if !libdir.is_dir() {
+        return Ok(shared);
+    }
+    // versions are dot separated numbers, before the extension on macos and after it everywhere else
+    let versioned = |v: &str| {
+        v.is_empty()
+            || v.strip_prefix('.')
+                .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit() || c == '.'))
+    };
+    let ext = format!(".{}", ToolChain::shared_lib_ext());
+    for entry in std::fs::read_dir(libdir)? {
+        let file = entry?.file_name().to_string_lossy().to_string();
+        let is_shared = binaries.iter().any(|b| {
+            let stem = format!("{}{}", ToolChain::shared_lib_prefix(), b.display());
+            file.strip_prefix(&stem).is_some_and(|rest| {
+                if cfg!(target_os = "macos") {
+                    rest.strip_suffix(&ext).is_some_and(versioned)
+                } else {
+                    rest.strip_prefix(&ext).is_some_and(versioned)
+                }
+            })
+        });
+        if is_shared {
+            shared.push(libdir.join(file));
+        }
+    }
+    shared.sort();
+    Ok(shared)
+}
+
+/// writes an executable shell script standing in for an external tool into a fresh scratch directory, returns both
+#[cfg(all(test, unix))]
+fn stub_tool(name: &str, script: &str) -> (PathBuf, PathBuf) {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("vango-test-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let stub = dir.join(name);
+    std::fs::write(&stub, script).unwrap();
+    std::fs::set_permissions(&stub, std::fs::Permissions::from_mode(0o755)).unwrap();
+    (dir, stub)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedup_link_order() {
+        // app -> [ylib, mid], mid (static) -> [ylib, Ws2_32]
+        let mut deps = Dependencies {
+            libdirs: vec!["../ylib/bin".into(), "../mid/bin".into(), "../ylib/bin".into()],
+            archives: vec!["ylib".into(), "mid".into(), "ylib".into(), "Ws2_32".into()],
+            ..Default::default()
+        };
+        deps.dedup();
+        assert_eq!(deps.libdirs, vec![PathBuf::from("../ylib/bin"), PathBuf::from("../mid/bin")]);
+        assert_eq!(deps.archives, vec![PathBuf::from("mid"), PathBuf::from("ylib"), PathBuf::from("Ws2_32")]);
+    }
+
+    #[test]
+    fn cycles_and_conflicts() {
+        let manifest = "[package]\nname = \"app\"\nversion = \"0.1.0\"\nlang = \"C++20\"\n\n[dependencies]\n";
+        let build = VangoFile::from_str(manifest).unwrap().unwrap_build();
+        let mut resolver = Resolver::new(&build, false, true).unwrap();
+        let root = resolver.stack[0].1.clone();
+        let (a, b) = (root.join("a"), root.join("b"));
+
+        resolver.enter("a", &a).unwrap();
+        resolver.enter("b", &b).unwrap();
+        let Err(Error::DependencyCycle(chain)) = resolver.enter("a", &a) else {
+            panic!("cycle not detected");
+        };
+        assert_eq!(chain, "a -> b -> a");
+        resolver.leave();
+        resolver.leave();
+        assert!(matches!(resolver.enter("app", &root), Err(Error::DependencyCycle(chain)) if chain == "app -> app"));
+
+        let v = |s: &str| s.parse::<Version>().unwrap();
+        resolver.register("c", v("1.0.0"), &a).unwrap();
+        resolver.register("c", v("1.0.0"), &a).unwrap();
+        resolver.register("c", v("1.0.0"), &b).unwrap();
+        assert!(matches!(resolver.register("c", v("2.0.0"), &b), Err(Error::VersionConflict(..))));
+    }
+
+    #[test]
+    fn shared_libraries_of_binaries() {
+        let dir = std::env::temp_dir().join(format!("vango-test-shared-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (prefix, ext) = (ToolChain::shared_lib_prefix(), ToolChain::shared_lib_ext());
+        let mut expected = vec![dir.join(format!("{prefix}foo.{ext}"))];
+        if cfg!(target_os = "macos") {
+            expected.push(dir.join("libfoo.1.dylib"));
+            expected.push(dir.join("libfoo.1.2.3.dylib"));
+        } else if cfg!(unix) {
+            expected.push(dir.join("libfoo.so.1"));
+            expected.push(dir.join("libfoo.so.1.2.3"));
+        }
+        let others = [
+            format!("{prefix}foobar.{ext}"),
+            format!("{prefix}bar.{ext}"),
+            "libfoo.a".into(),
+            "foo.lib".into(),
+        ];
+        for file in expected.iter().cloned().chain(others.iter().map(|o| dir.join(o))) {
+            std::fs::write(file, "").unwrap();
+        }
+
+        expected.sort();
+        assert_eq!(shared_libraries(&dir, &["foo".into()]).unwrap(), expected);
+        assert!(shared_libraries(&dir.join("missing"), &["foo".into()]).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,178 @@
+use super::Dependencies;
+use crate::error::Error;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// '$PKG_CONFIG' if set, like autotools and meson, otherwise 'pkg-config' from the PATH
+fn executable() -> PathBuf {
+    std::env::var_os("PKG_CONFIG")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("pkg-config"))
+}
+
+/// splits a version requirement into a pkg-config comparison and a version, a bare version is a minimum ('2.26' is '>= 2.26')
+pub fn requirement(version: &str) -> Option<(&str, &str)> {
+    let version = version.trim();
+    let (op, rest) = ["!=", ">=", "<=", "=", ">", "<"]
+        .into_iter()
+        .find_map(|op| version.strip_prefix(op).map(|rest| (op, rest.trim_start())))
+        .unwrap_or((">=", version));
+    let valid = !rest.is_empty()
+        && rest
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '~' | '+'));
+    valid.then_some((op, rest))
+}
+
+/// the flags of a module (and of the modules it requires) as reported by pkg-config, 'static_link' adds
+/// everything only needed to link it statically ('Libs.private' and 'Requires.private')
+pub fn probe(module: &str, version: Option<&str>, static_link: bool, msvc: bool) -> Result<Dependencies, Error> {
+    probe_with(&executable(), module, version, static_link, msvc)
+}
+
+/// a module and its constraint as a single argument, i.e. 'sdl2 >= 2.26', as understood by pkg-config and by 'Requires'
+pub fn spec(module: &str, version: Option<&str>) -> String {
+    match version.and_then(requirement) {
+        Some((op, version)) => format!("{module} {op} {version}"),
+        None => module.to_string(),
+    }
+}
+
+fn probe_with(exe: &Path, module: &str, version: Option<&str>, static_link: bool, msvc: bool) -> Result<Dependencies, Error> {
+    let spec = spec(module, version);
+    let query = |flags: &str| -> Result<String, Error> {
+        let mut cmd = Command::new(exe);
+        cmd.args(["--print-errors", flags]);
+        if static_link {
+            cmd.arg("--static");
+        }
+        let output = cmd.arg(&spec).output().map_err(|_| Error::PkgConfigNotFound)?;
+        if !output.status.success() {
+            let log = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::PkgConfigFail(module.to_string(), log.trim().to_string()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    let mut deps = Dependencies::default();
+    for flag in split(&query("--cflags")?) {
+        if let Some(dir) = flag.strip_prefix("-I") {
+            deps.incdirs.push(PathBuf::from(dir));
+        } else if let Some(def) = flag.strip_prefix("-D") {
+            deps.defines.push(def.to_string());
+        } else {
+            deps.compiler_options.push(flag);
+        }
+    }
+    for flag in split(&query("--libs")?) {
+        if let Some(dir) = flag.strip_prefix("-L") {
+            // modules outside of the default search paths are found again at runtime
+            deps.libdirs.push(PathBuf::from(dir));
+            deps.rpaths.push(PathBuf::from(dir));
+        } else if let Some(lib) = flag.strip_prefix("-l") {
+            deps.archives.push(if msvc {
+                PathBuf::from(lib).with_extension("lib")
+            } else {
+                PathBuf::from(lib)
+            });
+        } else {
+            deps.linker_options.push(flag);
+        }
+    }
+    Ok(deps)
+}
+
+/// splits pkg-config output into arguments, spaces escaped with a backslash are part of an argument
+fn split(output: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut chars = output.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a fake 'pkg-config' that records its arguments next to itself and knows 'sdl2' 2.30.0, which privately requires 'm'
+    #[cfg(unix)]
+    const STUB: &str = r#"#!/bin/sh
+echo "$@" >> "$(dirname "$0")/args.txt"
+for last; do :; done
+case "$last" in
+  "sdl2 >= 3.0") echo "Requested 'sdl2 >= 3.0' but version of SDL2 is 2.30.0" >&2; exit 1;;
+  sdl2*) ;;
+  *) echo "Package $last was not found in the pkg-config search path." >&2; exit 1;;
+esac
+case "$*" in
+  *--cflags*) echo '-I/opt/sdl\ 2/include/SDL2 -D_REENTRANT -pthread';;
+  *--static*) echo '-L/opt/sdl\ 2/lib -Wl,-rpath,/opt/sdl -lSDL2 -lm';;
+  *) echo '-L/opt/sdl\ 2/lib -lSDL2';;
+esac
+"#;
+
+    #[test]
+    fn requirements() {
+        assert_eq!(requirement(">=2.26"), Some((">=", "2.26")));
+        assert_eq!(requirement("< 3"), Some(("<", "3")));
+        assert_eq!(requirement("= 1.2.3-rc1"), Some(("=", "1.2.3-rc1")));
+        assert_eq!(requirement("2.26"), Some((">=", "2.26")));
+        assert_eq!(requirement(">="), None);
+        assert_eq!(requirement(">= 2, < 3"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn stub_probe() {
+        let (dir, stub) = crate::fetch::stub_tool("pkg-config", STUB);
+
+        let deps = probe_with(&stub, "sdl2", Some("2.26"), false, false).unwrap();
+        assert_eq!(deps.incdirs, [PathBuf::from("/opt/sdl 2/include/SDL2")]);
+        assert_eq!(deps.defines, ["_REENTRANT"]);
+        assert_eq!(deps.compiler_options, ["-pthread"]);
+        assert_eq!(deps.libdirs, [PathBuf::from("/opt/sdl 2/lib")]);
+        assert_eq!(deps.archives, [PathBuf::from("SDL2")]);
+        assert!(deps.linker_options.is_empty());
+
+        let deps = probe_with(&stub, "sdl2", None, true, true).unwrap();
+        assert_eq!(deps.archives, [PathBuf::from("SDL2.lib"), PathBuf::from("m.lib")]);
+        assert_eq!(deps.linker_options, ["-Wl,-rpath,/opt/sdl"]);
+
+        let err = probe_with(&stub, "sdl2", Some(">= 3.0"), false, false).unwrap_err();
+        assert!(err.to_string().contains("version of SDL2 is 2.30.0"));
+        assert!(probe_with(&stub, "gtk4", None, false, false).is_err());
+        assert!(matches!(
+            probe_with(&dir.join("missing"), "sdl2", None, false, false),
+            Err(Error::PkgConfigNotFound)
+        ));
+
+        let args = std::fs::read_to_string(dir.join("args.txt")).unwrap();
+        assert_eq!(
+            args.lines().take(4).collect::<Vec<_>>(),
+            [
+                "--print-errors --cflags sdl2 >= 2.26",
+                "--print-errors --libs sdl2 >= 2.26",
+                "--print-errors --cflags --static sdl2",
+                "--print-errors --libs --static sdl2",
+            ]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    config::{BuildSettings, DebugInfo, Lang, LibFile, LibProfile, Profile, Recipe, Runtime, ToolChain, Version},
+    error::Error,
+    input::BuildSwitches,
+    log_info_ln,
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// recipes are rerun whenever their declaration changes, the last successful one is recorded here
+const STAMP_FILE: &str = ".vango-recipe";
+
+/// install prefix of a recipe, separate per toolchain and profile like native builds
+pub fn prefix(root: &Path, toolchain: ToolChain, profile: &Profile) -> PathBuf {
+    root.join("bin")
+        .join("recipe")
+        .join(toolchain.as_directory())
+        .join(profile.to_string())
+}
+
+/// the include directory a recipe produces, without running it
+pub fn include_dir(root: &Path, recipe: &Recipe, toolchain: ToolChain, profile: &Profile) -> PathBuf {
+    match recipe.table().include {
+        Some(include) => root.join(include),
+        None => prefix(root, toolchain, profile).join("include"),
+    }
+}
+
+/// runs the recipe of a foreign dependency (unless up to date) and describes its outputs as a library,
+/// 'switches' are those of the recursive build (always a builtin profile), 'settings' those of the consumer
+pub fn build(
+    name: &str,
+    root: &Path,
+    recipe: &Recipe,
+    switches: &BuildSwitches,
+    settings: &BuildSettings,
+    lang: Lang,
+) -> Result<LibFile, Error> {
+    let mut table = recipe.table();
+    // scripts are declared relative to the consuming project, but run in the dependency root
+    if let Some(script) = &table.script {
+        let script = std::fs::canonicalize(script).map_err(|_| Error::RecipeToolNotFound(script.display().to_string(), name.to_string()))?;
+        table.script = Some(script);
+    }
+
+    let prefix = prefix(root, switches.toolchain, &switches.profile);
+    let env = environment(switches.toolchain, &switches.profile, settings, &prefix);
+    // the consumer's settings are part of the stamp, objects built with other flags may not link with it
+    let stamp = format!("{table:?}\n{env:?}");
+    if std::fs::read_to_string(prefix.join(STAMP_FILE)).ok().as_deref() != Some(stamp.as_str()) {
+        log_info_ln!("{:-<80}", format!("running recipe for dependency '{name}' "));
+        let _ = std::fs::remove_dir_all(&prefix);
+        std::fs::create_dir_all(&prefix)?;
+        let run = |program: &str, args: &[String]| run_step(name, root, &env, program, args, switches.verbose);
+        let release = switches.profile == Profile::Release;
+
+        if let Some(cmake) = &table.cmake {
+            let builddir = prefix.with_file_name(format!("{}-build", switches.profile));
+            let config = if release { "Release" } else { "Debug" };
+            let pic = if position_independent(switches.toolchain, settings) {
+                "ON"
+            } else {
+                "OFF"
+            };
+            let mut args = vec![
+                "-S".to_string(),
+                ".".to_string(),
+                "-B".to_string(),
+                builddir.display().to_string(),
+                format!("-DCMAKE_BUILD_TYPE={config}"),
+                format!("-DCMAKE_INSTALL_PREFIX={}", prefix.display()),
+                format!("-DCMAKE_POSITION_INDEPENDENT_CODE={pic}"),
+            ];
+            if switches.toolchain.is_msvc() {
+                // only honoured with policy CMP0091, which projects requiring CMake < 3.15 leave unset
+                args.push("-DCMAKE_POLICY_DEFAULT_CMP0091=NEW".to_string());
+                args.push(format!("-DCMAKE_MSVC_RUNTIME_LIBRARY={}", msvc_runtime_library(settings.runtime)));
+            }
+            args.extend(cmake.options.iter().cloned());
+            run("cmake", &args)?;
+            let builddir = builddir.display().to_string();
+            run("cmake", &["--build".into(), builddir.clone(), "--config".into(), config.into(), "--parallel".into()])?;
+            run("cmake", &["--install".into(), builddir, "--config".into(), config.into()])?;
+        } else if let Some(configure) = &table.configure {
+            let mut args = vec!["./configure".to_string(), format!("--prefix={}", prefix.display())];
+            args.extend(configure.options.iter().cloned());
+            run("sh", &args)?;
+            run("make", &[])?;
+            run("make", &["install".to_string()])?;
+        } else if let Some(make) = &table.make {
+            let mut args = vec![format!("PREFIX={}", prefix.display())];
+            args.extend(make.options.iter().cloned());
+            run("make", &[args.clone(), make.targets.clone()].concat())?;
+            // outputs are taken from the prefix, unless the recipe says where in the source tree they are
+            if table.include.is_none() || table.libdir.is_none() {
+                run("make", &[args, vec!["install".to_string()]].concat())?;
+            }
+        } else if let Some(script) = &table.script {
+            let script = script.display().to_string();
+            if cfg!(windows) {
+                run("cmd", &["/C".to_string(), script])?;
+            } else {
+                run("sh", &[script])?;
+            }
+        }
+        std::fs::write(prefix.join(STAMP_FILE), stamp)?;
+    }
+
+    let profile = LibProfile {
+        include: table.include.map(|i| root.join(i)).unwrap_or(prefix.join("include")),
+        libdir: table.libdir.map(|l| root.join(l)).unwrap_or(prefix.join("lib")),
+        binaries: if table.binaries.is_empty() {
+            vec![PathBuf::from(name)]
+        } else {
+            table.binaries
+        },
+        defines: table.defines,
+    };
+    Ok(LibFile {
+        name: name.to_string(),
+        // foreign projects carry no version vango understands, and are built for the consumer's standard
+        version: Version {
+            major: 0,
+            minor: 0,
+            patch: 0,
+        },
+        lang,
+        profiles: HashMap::from([(switches.profile.to_string(), profile)]),
+    })
+}
+
+/// maps the consumer's toolchain, profile and settings to the variables foreign build systems understand
+fn environment(toolchain: ToolChain, baseprof: &Profile, settings: &BuildSettings, prefix: &Path) -> Vec<(String, String)> {
+    let command_line = |cmd: Command| {
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|a| a.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let (cflags, ldflags) = flags(toolchain, settings);
+    vec![
+        ("CC".to_string(), command_line(toolchain.compiler(false))),
+        ("CXX".to_string(), command_line(toolchain.compiler(true))),
+        ("CFLAGS".to_string(), cflags.clone()),
+        ("CXXFLAGS".to_string(), cflags),
+        ("LDFLAGS".to_string(), ldflags),
+        ("VANGO_PREFIX".to_string(), prefix.display().to_string()),
+        ("VANGO_PROFILE".to_string(), baseprof.to_string()),
+        ("VANGO_TOOLCHAIN".to_string(), toolchain.as_directory().to_string()),
+    ]
+}
+
+/// foreign libraries may end up in any kind of consumer, so they are position independent whenever vango libraries are
+fn position_independent(toolchain: ToolChain, settings: &BuildSettings) -> bool {
+    settings.aslr && !cfg!(windows) && !toolchain.is_emcc()
+}
+
+fn msvc_runtime_library(runtime: Runtime) -> &'static str {
+    match runtime {
+        Runtime::DynamicDebug => "MultiThreadedDebugDLL",
+        Runtime::DynamicRelease => "MultiThreadedDLL",
+        Runtime::StaticDebug => "MultiThreadedDebug",
+        Runtime::StaticRelease => "MultiThreaded",
+    }
+}
+
+/// the compiler and linker flags of the consumer that foreign objects must agree with to link against it,
+/// i.e. optimization, debug info, position independence, the runtime and sanitizers
+fn flags(toolchain: ToolChain, settings: &BuildSettings) -> (String, String) {
+    let mut cflags: Vec<&str> = Vec::new();
+    let mut ldflags: Vec<&str> = Vec::new();
+    if toolchain.is_msvc() {
+        cflags.push(match settings.runtime {
+            Runtime::DynamicDebug => "/MDd",
+            Runtime::DynamicRelease => "/MD",
+            Runtime::StaticDebug => "/MTd",
+            Runtime::StaticRelease => "/MT",
+        });
+        cflags.push(match settings.opt_level {
+            0 => "/Od",
+            1 => "/Ox",
+            2 => "/O1",
+            _ => "/O2",
+        });
+        if settings.opt_size {
+            cflags.push("/Os");
+        }
+        if settings.debug_info != DebugInfo::None {
+            cflags.push("/Zi");
+        }
+        if settings.asan {
+            cflags.push("/fsanitize=address");
+        }
+    } else {
+        cflags.push(match settings.opt_level {
+            0 => "-O0",
+            1 => "-O1",
+            2 => "-O2",
+            _ => "-O3",
+        });
+        if settings.opt_size {
+            cflags.push("-Os");
+        }
+        if settings.debug_info != DebugInfo::None {
+            cflags.push("-g");
+        }
+        if position_independent(toolchain, settings) {
+            cflags.push("-fPIC");
+        }
+        // sanitizers and threads are needed when compiling and when linking
+        let mut both = Vec::new();
+        if settings.asan && (!cfg!(windows) || toolchain.is_clang()) {
+            both.push("-fsanitize=address");
+        }
+        if settings.tsan && !cfg!(windows) {
+            both.push("-fsanitize=thread");
+        }
+        if settings.lsan && !cfg!(windows) {
+            both.push("-fsanitize=leak");
+        }
+        if settings.ubsan && (!cfg!(windows) || toolchain.is_clang()) {
+            both.push("-fsanitize=undefined");
+        }
+        if settings.pthreads {
+            both.push("-pthread");
+        }
+        cflags.extend(&both);
+        ldflags.extend(&both);
+    }
+    (cflags.join(" "), ldflags.join(" "))
+}
+
+fn run_step(name: &str, root: &Path, env: &[(String, String)], program: &str, args: &[String], verbose: bool) -> Result<(), Error> {
+    let mut cmd = Command::new(program);
+    cmd.current_dir(root).args(args).envs(env.iter().map(|(k, v)| (k, v)));
+    let step = format!("{program} {}", args.join(" "));
+    let not_found = |_| Error::RecipeToolNotFound(program.to_string(), name.to_string());
+
+    if verbose {
+        let status = cmd.status().map_err(not_found)?;
+        if !status.success() {
+            return Err(Error::RecipeFail(name.to_string(), step, String::new()));
+        }
+    } else {
+        let output = cmd.output().map_err(not_found)?;
+        if !output.status.success() {
+            return Err(Error::RecipeFail(name.to_string(), step, super::output_tail(&output)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RecipeTable, RecipeTool, VangoFile};
+
+    /// the settings of the release profile of a manifest with 'profile' as its '[profile.release]' table
+    fn settings(profile: &str) -> BuildSettings {
+        let manifest =
+            format!("[package]\nname = \"app\"\nversion = \"0.1.0\"\nlang = \"C++20\"\n\n[dependencies]\n\n[profile.release]\n{profile}");
+        let build = VangoFile::from_str(&manifest).unwrap().unwrap_build();
+        build.get(&Profile::Release).unwrap().settings
+    }
+
+    #[test]
+    fn consumer_flags() {
+        let (cflags, ldflags) = flags(ToolChain::Msvc, &settings("runtime = \"static-release\""));
+        assert_eq!(cflags, "/MT /O2");
+        assert_eq!(ldflags, "");
+
+        let gcc = settings("opt-level = 1\ndebug-info = true\nsanitize.address = true");
+        let (cflags, ldflags) = flags(ToolChain::Gcc, &gcc);
+        if cfg!(windows) {
+            assert_eq!(cflags, "-O1 -g");
+        } else {
+            assert_eq!(cflags, "-O1 -g -fPIC -fsanitize=address");
+            assert_eq!(ldflags, "-fsanitize=address");
+        }
+        assert_eq!(msvc_runtime_library(Runtime::DynamicDebug), "MultiThreadedDebugDLL");
+    }
+
+    #[test]
+    fn script_recipe() {
+        if cfg!(windows) {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("vango-test-recipe-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("dep")).unwrap();
+        std::fs::write(
+            dir.join("build.sh"),
+            "mkdir -p \"$VANGO_PREFIX/include\" && echo \"$VANGO_PROFILE\" > \"$VANGO_PREFIX/include/profile.txt\"\n",
+        )
+        .unwrap();
+        let recipe = Recipe::Table(Box::new(RecipeTable {
+            script: Some(dir.join("build.sh")),
+            binaries: vec!["dep".into()],
+            ..Default::default()
+        }));
+        let switches = BuildSwitches {
+            toolchain: ToolChain::Gcc,
+            ..Default::default()
+        };
+        let settings = settings("");
+
+        let mut lib = build("dep", &dir.join("dep"), &recipe, &switches, &settings, Lang::Cpp(117)).unwrap();
+        let profile = lib.take(&Profile::Debug).unwrap();
+        let prefix = prefix(&dir.join("dep"), ToolChain::Gcc, &Profile::Debug);
+        assert_eq!(profile.include, prefix.join("include"));
+        assert_eq!(profile.libdir, prefix.join("lib"));
+        assert_eq!(std::fs::read_to_string(prefix.join("include/profile.txt")).unwrap().trim(), "debug");
+        assert!(prefix.join(STAMP_FILE).exists());
+
+        let failing = Recipe::Table(Box::new(RecipeTable {
+            make: Some(RecipeTool::default()),
+            ..Default::default()
+        }));
+        assert!(build("dep", &dir.join("dep"), &failing, &switches, &settings, Lang::Cpp(117)).is_err());
+
+        // make recipes install into the prefix, unless they say where their outputs are
+        std::fs::write(
+            dir.join("dep/Makefile"),
+            "all:\n\ttouch built.txt\ninstall:\n\tmkdir -p $(PREFIX)/include && cp built.txt $(PREFIX)/include\n",
+        )
+        .unwrap();
+        build("dep", &dir.join("dep"), &failing, &switches, &settings, Lang::Cpp(117)).unwrap();
+        assert!(prefix.join("include/built.txt").exists());
+        let in_tree = Recipe::Table(Box::new(RecipeTable {
+            make: Some(RecipeTool::default()),
+            include: Some(".".into()),
+            libdir: Some(".".into()),
+            ..Default::default()
+        }));
+        build("dep", &dir.join("dep"), &in_tree, &switches, &settings, Lang::Cpp(117)).unwrap();
+        assert!(!prefix.join("include").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
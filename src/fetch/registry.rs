@@ -0,0 +1,234 @@
+use super::{Resolver, archive, git, git::GitRef, lock};
+use crate::{
+    config::{RegistryConfig, Version, VersionReq},
+    error::Error,
+    log_info_ln,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// every package has one index file at 'index/<name>.toml' relative to the registry root
+pub const INDEX_DIR: &str = "index";
+/// 'vango publish' places archives at 'packages/<name>/<name>-<version>.tar.gz' relative to the registry root
+pub const PACKAGE_DIR: &str = "packages";
+
+/// files and directories of a project that are never part of a published package
+const EXCLUDED: [&str; 5] = ["bin", "vendor", ".git", ".vango", "Vango.lock"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexFile {
+    #[serde(default, rename = "version")]
+    pub versions: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub version: String,
+    /// location of the package archive, relative to the registry root or an http(s) url
+    pub archive: String,
+    pub sha256: String,
+}
+
+impl IndexFile {
+    pub fn load(root: &Path, name: &str) -> Result<Self, Error> {
+        match std::fs::read_to_string(root.join(INDEX_DIR).join(format!("{name}.toml"))) {
+            Ok(file) => Ok(toml::from_str(&file)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::FileSystem(e)),
+        }
+    }
+
+    pub fn save(&self, root: &Path, name: &str) -> Result<(), Error> {
+        std::fs::create_dir_all(root.join(INDEX_DIR))?;
+        let body = toml::to_string(self).expect("index is always serializable");
+        std::fs::write(root.join(INDEX_DIR).join(format!("{name}.toml")), body)?;
+        Ok(())
+    }
+
+    pub fn get(&self, version: Version) -> Option<&IndexEntry> {
+        self.versions.iter().find(|e| Version::from_str(&e.version).ok() == Some(version))
+    }
+
+    /// the newest version matching the requirement
+    pub fn select(&self, req: &VersionReq) -> Option<(Version, &IndexEntry)> {
+        self.versions
+            .iter()
+            .filter_map(|e| Some((Version::from_str(&e.version).ok()?, e)))
+            .filter(|(v, _)| req.matches(v))
+            .max_by_key(|(v, _)| *v)
+    }
+
+    fn pick(&self, locked: Option<Version>, req: &VersionReq) -> Option<(Version, IndexEntry)> {
+        match locked {
+            Some(v) => self.get(v).map(|e| (v, e.clone())),
+            None => self.select(req).map(|(v, e)| (v, e.clone())),
+        }
+    }
+}
+
+/// location of a registry package relative to the vendor directory
+pub fn vendor_path(name: &str, version: Version) -> PathBuf {
+    PathBuf::from("registry").join(format!("{name}-{version}"))
+}
+
+/// registry packages are cached per checksum, versions alone are only unique within one registry
+fn cache_path(name: &str, version: Version, sha256: &str) -> PathBuf {
+    std::env::home_dir()
+        .unwrap()
+        .join(".vango/packages/registry")
+        .join(format!("{name}-{version}-{}", &sha256[..sha256.len().min(16)]))
+}
+
+/// local root of a registry, git registries are cloned into '~/.vango/registries'
+fn registry_root(name: &str, config: &RegistryConfig, resolver: &mut Resolver) -> Result<PathBuf, Error> {
+    if !config.is_git() {
+        return Ok(PathBuf::from(&config.index));
+    }
+    let root = std::env::home_dir()
+        .unwrap()
+        .join(".vango/registries")
+        .join(super::git_cache_key(&config.index));
+    if !std::fs::exists(&root)? {
+        if resolver.offline {
+            return Err(Error::OfflineFetch(format!("registry '{name}'")));
+        }
+        log_info_ln!("{:-<80}", format!("cloning registry index '{name}' to: {} ", root.display()));
+        git::clone(&config.index, &GitRef::DefaultBranch, &root)?;
+        resolver.refreshed.insert(name.to_string());
+    }
+    Ok(root)
+}
+
+/// fetches the newest index of a git registry at most once per invocation, returns whether anything was fetched
+fn refresh(name: &str, config: &RegistryConfig, root: &Path, resolver: &mut Resolver) -> Result<bool, Error> {
+    if !config.is_git() || resolver.offline || !resolver.refreshed.insert(name.to_string()) {
+        return Ok(false);
+    }
+    log_info_ln!("updating registry index: {name}");
+    git::update(root, &GitRef::DefaultBranch)?;
+    Ok(true)
+}
+
+/// resolves a registry dependency to an extracted package, the locked version is kept as long as it satisfies the requirement
+pub fn resolve(name: &str, version: &str, registry: Option<&str>, resolver: &mut Resolver) -> Result<(PathBuf, String), Error> {
+    let req = VersionReq::from_str(version)?;
+    let (regname, config) = resolver.config.registry(registry)?;
+    let (regname, config) = (regname.to_string(), config.clone());
+    let source = lock::registry_source(&config.index);
+
+    let locked = resolver.locked_version(name, &source).filter(|v| req.matches(v));
+    if locked.is_none() && resolver.locked {
+        return Err(Error::LockOutdated(name.to_string()));
+    }
+    let locked_checksum = resolver.lock.get(name, &source).and_then(|p| p.checksum.clone());
+
+    if let Some(vendor) = &resolver.vendor {
+        let (Some(version), Some(checksum)) = (locked, locked_checksum) else {
+            return Err(Error::NotVendored(name.to_string()));
+        };
+        let path = vendor.join(vendor_path(name, version));
+        if !std::fs::exists(&path)? {
+            return Err(Error::NotVendored(name.to_string()));
+        }
+        resolver.lock_registry(name, &source, version, &checksum)?;
+        return Ok((path, source));
+    }
+
+    let root = registry_root(&regname, &config, resolver)?;
+    let mut entry = IndexFile::load(&root, name)?.pick(locked, &req);
+    if entry.is_none() && refresh(&regname, &config, &root, resolver)? {
+        entry = IndexFile::load(&root, name)?.pick(locked, &req);
+    }
+    let (version, entry) = entry.ok_or(Error::NoMatchingVersion(name.to_string(), version.to_string(), regname))?;
+    // a published version must never change, the lockfile detects a rewritten index
+    if let Some(checksum) = locked_checksum.filter(|c| locked.is_some() && *c != entry.sha256) {
+        return Err(Error::ChecksumMismatch(name.to_string(), checksum, entry.sha256));
+    }
+
+    let path = cache_path(name, version, &entry.sha256);
+    if !std::fs::exists(&path)? {
+        if resolver.offline {
+            return Err(Error::OfflineFetch(name.to_string()));
+        }
+        log_info_ln!("{:-<80}", format!("downloading registry package '{name}' v{version} "));
+        let data = archive::download(&root, &entry.archive)?;
+        archive::verify(name, &data, &entry.sha256)?;
+        archive::unpack_tar_gz(&data, &path)?;
+    }
+    resolver.lock_registry(name, &source, version, &entry.sha256)?;
+    Ok((path, source))
+}
+
+/// creates a gzipped tarball of a project, without build output or local state
+pub fn pack(root: &Path) -> Result<Vec<u8>, Error> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    append_dir(&mut builder, root, Path::new(""))?;
+    Ok(builder.into_inner()?.finish()?)
+}
+
+fn append_dir<W: std::io::Write>(builder: &mut tar::Builder<W>, root: &Path, rel: &Path) -> Result<(), Error> {
+    let mut entries = std::fs::read_dir(root.join(rel))?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(std::fs::DirEntry::file_name);
+    for e in entries {
+        let name = e.file_name();
+        if (rel.as_os_str().is_empty() && EXCLUDED.iter().any(|x| name == *x)) || name == ".git" {
+            continue;
+        }
+        if e.path().is_dir() {
+            append_dir(builder, root, &rel.join(&name))?;
+        } else {
+            builder.append_path_with_name(e.path(), rel.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_select() {
+        let entry = |v: &str| IndexEntry {
+            version: v.to_string(),
+            archive: format!("packages/fmt/fmt-{v}.tar.gz"),
+            sha256: String::new(),
+        };
+        let index = IndexFile {
+            versions: vec![entry("10.1.0"), entry("10.2.1"), entry("10.2.0"), entry("11.0.0")],
+        };
+        let req = |s: &str| VersionReq::from_str(s).unwrap();
+        let v = |s: &str| Version::from_str(s).unwrap();
+        assert_eq!(index.select(&req("10.2")).unwrap().0, v("10.2.1"));
+        assert_eq!(index.select(&req("~10.1")).unwrap().0, v("10.1.0"));
+        assert_eq!(index.select(&req("*")).unwrap().0, v("11.0.0"));
+        assert!(index.select(&req("12")).is_none());
+        assert_eq!(index.pick(Some(v("10.2.0")), &req("10.2")).unwrap().0, v("10.2.0"));
+
+        let text = toml::to_string(&index).unwrap();
+        assert_eq!(toml::from_str::<IndexFile>(&text).unwrap(), index);
+    }
+
+    #[test]
+    fn pack_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("vango-test-registry-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("pkg/src")).unwrap();
+        std::fs::create_dir_all(dir.join("pkg/bin/debug")).unwrap();
+        std::fs::write(dir.join("pkg/Vango.toml"), "[package]").unwrap();
+        std::fs::write(dir.join("pkg/src/main.cpp"), "int main() {}").unwrap();
+        std::fs::write(dir.join("pkg/bin/debug/main.o"), "").unwrap();
+
+        let archive = pack(&dir.join("pkg")).unwrap();
+        assert_eq!(archive::sha256_hex(&archive).len(), 64);
+        archive::unpack_tar_gz(&archive, &dir.join("out")).unwrap();
+        assert!(dir.join("out/Vango.toml").exists());
+        assert!(dir.join("out/src/main.cpp").exists());
+        assert!(!dir.join("out/bin").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{config::VcpkgConfig, error::Error, log_info_ln};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VcpkgDependency {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    features: Vec<String>,
+    /// exact version, pinned through an override (requires a baseline)
+    #[serde(skip)]
+    version: Option<String>,
+}
+
+impl VcpkgDependency {
+    pub fn new(name: &str, features: &[String], version: Option<&str>) -> Self {
+        Self {
+            name: name.to_ascii_lowercase(),
+            features: features.to_vec(),
+            version: version.map(str::to_string),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    dependencies: &'a [VcpkgDependency],
+    #[serde(rename = "builtin-baseline", skip_serializing_if = "Option::is_none")]
+    baseline: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    overrides: Vec<Override<'a>>,
+}
+
+#[derive(Serialize)]
+struct Override<'a> {
+    name: &'a str,
+    version: &'a str,
+}
+
+/// '$VCPKG_ROOT/vcpkg' if set, like vcpkg's own tooling, otherwise 'vcpkg' from the PATH
+fn executable() -> PathBuf {
+    match std::env::var_os("VCPKG_ROOT") {
+        Some(root) => PathBuf::from(root).join(if cfg!(windows) { "vcpkg.exe" } else { "vcpkg" }),
+        None => PathBuf::from("vcpkg"),
+    }
+}
+
+/// runs 'vcpkg install' in manifest mode for the current project, returns the installed tree
+pub fn install(packages: &[VcpkgDependency], config: &VcpkgConfig) -> Result<PathBuf, Error> {
+    install_with(&executable(), Path::new("bin"), packages, config)
+}
+
+fn install_with(vcpkg: &Path, dir: &Path, packages: &[VcpkgDependency], config: &VcpkgConfig) -> Result<PathBuf, Error> {
+    std::fs::create_dir_all(dir)?;
+    let manifest = Manifest {
+        dependencies: packages,
+        baseline: config.baseline.as_deref(),
+        overrides: packages
+            .iter()
+            .filter_map(|p| {
+                Some(Override {
+                    name: &p.name,
+                    version: p.version.as_deref()?,
+                })
+            })
+            .collect(),
+    };
+    std::fs::write(dir.join("vcpkg.json"), serde_json::to_string_pretty(&manifest).unwrap())?;
+
+    log_info_ln!("{:-<80}", format!("pulling vcpkg dependencies "));
+    let installed = dir.join("vcpkg_installed");
+    let mut cmd = Command::new(vcpkg);
+    cmd.current_dir(dir)
+        .arg("install")
+        .arg(format!("--triplet={}", config.triplet))
+        .arg(format!("--x-install-root={}", std::path::absolute(&installed)?.display()));
+    // overlays are declared relative to the project, but vcpkg runs in 'bin'
+    for overlay in &config.overlay_ports {
+        cmd.arg(format!("--overlay-ports={}", std::path::absolute(overlay)?.display()));
+    }
+    let output = cmd.output().map_err(|_| Error::VcpkgNotFound)?;
+    if !output.status.success() {
+        return Err(Error::VcpkgFail(super::output_tail(&output)));
+    }
+    Ok(installed)
+}
+
+/// the libraries of 'packages' and everything they depend on, as installed for 'triplet', in link order (dependents
+/// before their dependencies) and as expected by the linker - names for gnu-like toolchains, file names for msvc
+pub fn libraries(installed: &Path, triplet: &str, packages: &[&str], debug: bool, msvc: bool) -> Result<Vec<PathBuf>, Error> {
+    let status = std::fs::read_to_string(installed.join("vcpkg").join("status"))?;
+    let mut depends: HashMap<String, Vec<String>> = HashMap::new();
+    for para in status.split("\n\n") {
+        let field = |key: &str| para.lines().find_map(|l| l.strip_prefix(key)?.strip_prefix(':').map(str::trim));
+        let (Some(name), Some(arch), Some(state)) = (field("Package"), field("Architecture"), field("Status")) else {
+            continue;
+        };
+        if arch != triplet || !state.ends_with(" installed") {
+            continue;
+        }
+        // feature paragraphs add to the dependencies of their package, host tools are never linked
+        let entry = depends.entry(name.to_string()).or_default();
+        for dep in field("Depends").into_iter().flat_map(|d| d.split(',')) {
+            let dep = dep.trim();
+            if !dep.is_empty() && !dep.ends_with(":host") {
+                entry.push(dep.split(':').next().unwrap().to_string());
+            }
+        }
+    }
+
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    for name in packages {
+        post_order(&name.to_ascii_lowercase(), &depends, &mut visited, &mut order);
+    }
+    order.reverse();
+
+    let libdir = if debug {
+        format!("{triplet}/debug/lib/")
+    } else {
+        format!("{triplet}/lib/")
+    };
+    let info = installed.join("vcpkg").join("info");
+    let mut libs = Vec::new();
+    for name in order {
+        let suffix = format!("_{triplet}.list");
+        let Some(list) = std::fs::read_dir(&info)?.filter_map(|e| e.ok()).map(|e| e.path()).find(|p| {
+            p.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .strip_prefix(&format!("{name}_"))
+                .is_some_and(|r| r.ends_with(&suffix))
+        }) else {
+            continue;
+        };
+        for line in std::fs::read_to_string(list)?.lines() {
+            // only files directly in 'lib', not 'lib/pkgconfig' or 'lib/cmake'
+            let Some(file) = line.strip_prefix(&libdir).filter(|f| !f.contains('/')) else {
+                continue;
+            };
+            if msvc {
+                if file.ends_with(".lib") {
+                    libs.push(PathBuf::from(file));
+                }
+            } else if let Some(stem) = [".a", ".so", ".dylib"].iter().find_map(|ext| file.strip_suffix(ext)) {
+                libs.push(PathBuf::from(stem.strip_prefix("lib").unwrap_or(stem)));
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    libs.retain(|l| seen.insert(l.clone()));
+    Ok(libs)
+}
+
+fn post_order(name: &str, depends: &HashMap<String, Vec<String>>, visited: &mut HashSet<String>, order: &mut Vec<String>) {
+    if !visited.insert(name.to_string()) {
+        return;
+    }
+    for dep in depends.get(name).into_iter().flatten() {
+        if depends.contains_key(dep) {
+            post_order(dep, depends, visited, order);
+        }
+    }
+    order.push(name.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a fake 'vcpkg' that records its arguments and installs 'fmt' depending on 'zlib', or fails if asked to
+    #[cfg(unix)]
+    const STUB: &str = r#"#!/bin/sh
+echo "$@" > args.txt
+grep -q broken vcpkg.json && { echo "error: no port named broken"; exit 1; }
+for a in "$@"; do case "$a" in --x-install-root=*) root="${a#--x-install-root=}";; esac; done
+mkdir -p "$root/vcpkg/info" "$root/x64-linux/lib" "$root/x64-linux/debug/lib"
+cat > "$root/vcpkg/status" <<EOF
+Package: zlib
+Version: 1.3.1
+Architecture: x64-linux
+Status: install ok installed
+
+Package: vcpkg-cmake
+Version: 2024-04-23
+Architecture: x64-linux
+Status: install ok installed
+
+Package: fmt
+Version: 10.2.1
+Depends: vcpkg-cmake:x64-linux, zlib
+Architecture: x64-linux
+Status: install ok installed
+
+EOF
+printf 'x64-linux/lib/libz.a\nx64-linux/debug/lib/libzd.a\nx64-linux/lib/pkgconfig/zlib.pc\n' > "$root/vcpkg/info/zlib_1.3.1_x64-linux.list"
+printf 'x64-linux/include/fmt/core.h\nx64-linux/lib/libfmt.a\nx64-linux/debug/lib/libfmtd.a\n' > "$root/vcpkg/info/fmt_10.2.1_x64-linux.list"
+"#;
+
+    #[test]
+    #[cfg(unix)]
+    fn stub_install() {
+        let (dir, stub) = crate::fetch::stub_tool("vcpkg", STUB);
+        let config = VcpkgConfig {
+            triplet: "x64-linux".to_string(),
+            baseline: Some("0123abcd".to_string()),
+            overlay_ports: vec![dir.join("ports")],
+        };
+
+        let packages = [VcpkgDependency::new("FMT", &[], Some("10.2.1"))];
+        let installed = install_with(&stub, &dir.join("bin"), &packages, &config).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(dir.join("bin/vcpkg.json")).unwrap()).unwrap();
+        assert_eq!(
+            manifest,
+            serde_json::json!({
+                "dependencies": [{ "name": "fmt" }],
+                "builtin-baseline": "0123abcd",
+                "overrides": [{ "name": "fmt", "version": "10.2.1" }],
+            })
+        );
+        let args = std::fs::read_to_string(dir.join("bin/args.txt")).unwrap();
+        assert!(args.starts_with("install --triplet=x64-linux --x-install-root="));
+        assert!(args.contains(&format!("--overlay-ports={}", dir.join("ports").display())));
+
+        let libs = |packages: &[&str], debug: bool| libraries(&installed, "x64-linux", packages, debug, false).unwrap();
+        assert_eq!(libs(&["fmt"], false), vec![PathBuf::from("fmt"), PathBuf::from("z")]);
+        assert_eq!(libs(&["zlib", "FMT"], true), vec![PathBuf::from("fmtd"), PathBuf::from("zd")]);
+        assert_eq!(libs(&["zlib"], false), vec![PathBuf::from("z")]);
+
+        let broken = [VcpkgDependency::new("broken", &[], None)];
+        let err = install_with(&stub, &dir.join("bin"), &broken, &config).unwrap_err();
+        assert!(err.to_string().contains("no port named broken"));
+        assert!(matches!(
+            install_with(&dir.join("missing"), &dir.join("bin"), &broken, &config),
+            Err(Error::VcpkgNotFound)
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
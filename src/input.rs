@@ -1,7 +1,8 @@
 use crate::{
-    config::{Profile, ToolChain},
+    config::{Dependency, Profile, ToolChain},
     error::Error,
 };
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
@@ -20,6 +21,28 @@ pub enum Action {
     },
     Clean,
     Clangd,
+    Update {
+        package: Option<String>,
+    },
+    Vendor,
+    Publish {
+        registry: PathBuf,
+    },
+    Add {
+        name: String,
+        dependency: Dependency,
+        dev: bool,
+    },
+    Remove {
+        name: String,
+        dev: bool,
+    },
+    Tree {
+        duplicates: bool,
+        invert: Option<String>,
+        format: TreeFormat,
+        offline: bool,
+    },
     #[allow(dead_code)]
     Gen {
         target: String,
@@ -35,6 +58,19 @@ pub enum Action {
         switches: BuildSwitches,
         args: Vec<String>,
     },
+    Install {
+        switches: BuildSwitches,
+        prefix: Option<PathBuf>,
+        destdir: Option<PathBuf>,
+    },
+    Uninstall {
+        prefix: Option<PathBuf>,
+        destdir: Option<PathBuf>,
+    },
+    Package {
+        switches: BuildSwitches,
+        profiles: Vec<Profile>,
+    },
     Help {
         action: Option<String>,
     },
@@ -46,12 +82,20 @@ pub enum Action {
 pub struct BuildSwitches {
     pub profile: Profile,
     pub toolchain: ToolChain,
-    pub install: bool,
+    pub locked: bool,
+    pub offline: bool,
     pub echo: bool,
     pub verbose: bool,
     pub is_test: bool,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TreeFormat {
+    #[default]
+    Text,
+    Dot,
+}
+
 pub fn collect_args() -> Result<Action, Error> {
     let mut args: Vec<_> = std::env::args().collect();
     // if let Some(first) = args.first() && first.as_str() == std::env::current_exe()?.as_os_str() {
@@ -102,7 +146,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
             let debug = args.remove_if(|s| *s == "--debug" || *s == "-d").is_some();
             let release = args.remove_if(|s| *s == "--release" || *s == "-r").is_some();
             let toolchain = parse_toolchain(args.remove_if(|s| s.starts_with("--toolchain=") || s.starts_with("-t=")))?;
-            let install = args.remove_if(|s| *s == "--install").is_some();
+            let locked = args.remove_if(|s| *s == "--locked").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
             let echo = args.remove_if(|s| *s == "--echo").is_some();
             let verbose = args.remove_if(|s| *s == "--verbose" || *s == "-v").is_some();
             let profile = parse_profile(args.remove_if(|s| s.starts_with("--profile=")), debug, release)?;
@@ -111,7 +156,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                     switches: BuildSwitches {
                         profile,
                         toolchain,
-                        install,
+                        locked,
+                        offline,
                         echo,
                         verbose,
                         is_test: false,
@@ -132,7 +178,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
             let debug = args.remove_if(|s| *s == "--debug" || *s == "-d").is_some();
             let release = args.remove_if(|s| *s == "--release" || *s == "-r").is_some();
             let toolchain = parse_toolchain(args.remove_if(|s| s.starts_with("--toolchain=") || s.starts_with("-t=")))?;
-            let install = args.remove_if(|s| *s == "--install").is_some();
+            let locked = args.remove_if(|s| *s == "--locked").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
             let echo = args.remove_if(|s| *s == "--echo").is_some();
             let verbose = args.remove_if(|s| *s == "--verbose" || *s == "-v").is_some();
             let profile = parse_profile(args.remove_if(|s| s.starts_with("--profile=")), debug, release)?;
@@ -141,7 +188,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                     switches: BuildSwitches {
                         profile,
                         toolchain,
-                        install,
+                        locked,
+                        offline,
                         echo,
                         verbose,
                         is_test: false,
@@ -156,7 +204,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
             let debug = args.remove_if(|s| *s == "--debug" || *s == "-d").is_some();
             let release = args.remove_if(|s| *s == "--release" || *s == "-r").is_some();
             let toolchain = parse_toolchain(args.remove_if(|s| s.starts_with("--toolchain=") || s.starts_with("-t=")))?;
-            let install = args.remove_if(|s| *s == "--install").is_some();
+            let locked = args.remove_if(|s| *s == "--locked").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
             let echo = args.remove_if(|s| *s == "--echo").is_some();
             let verbose = args.remove_if(|s| *s == "--verbose" || *s == "-v").is_some();
             let profile = parse_profile(args.remove_if(|s| s.starts_with("--profile=")), debug, release)?;
@@ -164,7 +213,8 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                 switches: BuildSwitches {
                     profile,
                     toolchain,
-                    install,
+                    locked,
+                    offline,
                     echo,
                     verbose,
                     is_test: true,
@@ -172,6 +222,74 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                 args,
             })
         }
+        "install" => {
+            let (prefix, destdir) = parse_prefix("install", &mut args)?;
+            let toolchain = parse_toolchain(args.remove_if(|s| s.starts_with("--toolchain=") || s.starts_with("-t=")))?;
+            let locked = args.remove_if(|s| *s == "--locked").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
+            let echo = args.remove_if(|s| *s == "--echo").is_some();
+            let verbose = args.remove_if(|s| *s == "--verbose" || *s == "-v").is_some();
+            if args.is_empty() {
+                Ok(Action::Install {
+                    switches: BuildSwitches {
+                        profile: Profile::Release,
+                        toolchain,
+                        locked,
+                        offline,
+                        echo,
+                        verbose,
+                        is_test: false,
+                    },
+                    prefix,
+                    destdir,
+                })
+            } else {
+                Err(Error::ExtraArgs("install".to_string(), args))
+            }
+        }
+        "uninstall" => {
+            let (prefix, destdir) = parse_prefix("uninstall", &mut args)?;
+            if args.is_empty() {
+                Ok(Action::Uninstall { prefix, destdir })
+            } else {
+                Err(Error::ExtraArgs("uninstall".to_string(), args))
+            }
+        }
+        "package" => {
+            let profiles = match remove_value(&mut args, &["--profiles"]) {
+                Some(Some(list)) => list
+                    .split(',')
+                    .map(|p| match p.trim() {
+                        "debug" => Profile::Debug,
+                        "release" => Profile::Release,
+                        p => Profile::Custom(p.to_string()),
+                    })
+                    .collect(),
+                Some(None) => return Err(Error::MissingOption("package".to_string(), "--profiles <LIST>".to_string())),
+                None => vec![Profile::Debug, Profile::Release],
+            };
+            let toolchain = parse_toolchain(args.remove_if(|s| s.starts_with("--toolchain=") || s.starts_with("-t=")))?;
+            let locked = args.remove_if(|s| *s == "--locked").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
+            let echo = args.remove_if(|s| *s == "--echo").is_some();
+            let verbose = args.remove_if(|s| *s == "--verbose" || *s == "-v").is_some();
+            if args.is_empty() {
+                Ok(Action::Package {
+                    switches: BuildSwitches {
+                        profile: Profile::Release,
+                        toolchain,
+                        locked,
+                        offline,
+                        echo,
+                        verbose,
+                        is_test: false,
+                    },
+                    profiles,
+                })
+            } else {
+                Err(Error::ExtraArgs("package".to_string(), args))
+            }
+        }
         "clean" | "c" => {
             if args.is_empty() {
                 Ok(Action::Clean)
@@ -186,6 +304,143 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                 Err(Error::ExtraArgs("gen".to_string(), args))
             }
         }
+        "update" => {
+            if args.len() <= 1 {
+                Ok(Action::Update { package: args.pop() })
+            } else {
+                Err(Error::ExtraArgs("update".to_string(), args))
+            }
+        }
+        "publish" => {
+            let Some(Some(registry)) = remove_value(&mut args, &["--registry"]) else {
+                return Err(Error::MissingOption("publish".to_string(), "--registry <DIR>".to_string()));
+            };
+            if args.is_empty() {
+                Ok(Action::Publish {
+                    registry: PathBuf::from(registry),
+                })
+            } else {
+                Err(Error::ExtraArgs("publish".to_string(), args))
+            }
+        }
+        "add" => {
+            let dev = args.remove_if(|s| *s == "--dev").is_some();
+            let system = args.remove_if(|s| *s == "--system").is_some();
+            let mut value = |names: &[&str], option: &str| match remove_value(&mut args, names) {
+                Some(Some(value)) => Ok(Some(value)),
+                Some(None) => Err(Error::MissingOption("add".to_string(), option.to_string())),
+                None => Ok(None),
+            };
+            let path = value(&["--path"], "--path <DIR>")?;
+            let headers = value(&["--headers"], "--headers <DIR>")?;
+            let git = value(&["--git"], "--git <URL>")?;
+            let tag = value(&["--tag"], "--tag <TAG>")?;
+            let branch = value(&["--branch"], "--branch <BRANCH>")?;
+            let rev = value(&["--rev"], "--rev <REV>")?;
+            let version = value(&["--version"], "--version <REQ>")?;
+            let registry = value(&["--registry"], "--registry <NAME>")?;
+            let features: Vec<String> = value(&["--features", "-F"], "--features <FEATURES>")?
+                .map(|f| f.split(',').map(|f| f.trim().to_string()).filter(|f| !f.is_empty()).collect())
+                .unwrap_or_default();
+            if args.len() > 1 {
+                return Err(Error::ExtraArgs("add".to_string(), args.split_off(1)));
+            }
+            let Some(name) = args.pop() else {
+                return Err(Error::MissingOption("add".to_string(), "<NAME>".to_string()));
+            };
+            // 'fmt@10.2' is shorthand for '--version 10.2'
+            let (name, version) = match name.split_once('@') {
+                Some((name, req)) if version.is_none() => (name.to_string(), Some(req.to_string())),
+                _ => (name, version),
+            };
+            if git.is_none() && (tag.is_some() || branch.is_some() || rev.is_some()) {
+                return Err(Error::MissingOption("add".to_string(), "--git <URL>".to_string()));
+            }
+            if version.is_none() && registry.is_some() {
+                return Err(Error::MissingOption("add".to_string(), "--version <REQ>".to_string()));
+            }
+            let sources = [path.is_some(), headers.is_some(), git.is_some(), version.is_some(), system];
+            if sources.into_iter().filter(|s| *s).count() != 1 {
+                return Err(Error::AmbiguousDependency(name));
+            }
+            let dependency = if let Some(src) = path {
+                Dependency::Package {
+                    src: src.into(),
+                    targets: Vec::new(),
+                    recipe: None,
+                    features,
+                    version: None,
+                }
+            } else if let Some(headers) = headers {
+                Dependency::Headers {
+                    headers: headers.into(),
+                    features,
+                }
+            } else if let Some(git) = git {
+                Dependency::Git {
+                    git,
+                    tag,
+                    branch,
+                    rev,
+                    path: None,
+                    headers: None,
+                    recipe: None,
+                    features,
+                }
+            } else if let Some(version) = version {
+                Dependency::Registry {
+                    version,
+                    registry,
+                    features,
+                }
+            } else {
+                Dependency::System {
+                    system: name.clone().into(),
+                }
+            };
+            Ok(Action::Add { name, dependency, dev })
+        }
+        "remove" | "rm" => {
+            let dev = args.remove_if(|s| *s == "--dev").is_some();
+            match args.len() {
+                0 => Err(Error::MissingOption("remove".to_string(), "<NAME>".to_string())),
+                1 => Ok(Action::Remove { name: args.remove(0), dev }),
+                _ => Err(Error::ExtraArgs("remove".to_string(), args.split_off(1))),
+            }
+        }
+        "tree" => {
+            let duplicates = args.remove_if(|s| *s == "--duplicates" || *s == "-d").is_some();
+            let offline = args.remove_if(|s| *s == "--offline").is_some();
+            let invert = match remove_value(&mut args, &["--invert", "-i"]) {
+                Some(Some(package)) => Some(package),
+                Some(None) => return Err(Error::MissingOption("tree".to_string(), "--invert <PACKAGE>".to_string())),
+                None => None,
+            };
+            let format = match remove_value(&mut args, &["--format"]) {
+                None => TreeFormat::Text,
+                Some(Some(f)) if f == "text" => TreeFormat::Text,
+                Some(Some(f)) if f == "dot" => TreeFormat::Dot,
+                Some(Some(f)) => return Err(Error::UnknownTreeFormat(f)),
+                Some(None) => return Err(Error::MissingOption("tree".to_string(), "--format <text|dot>".to_string())),
+            };
+            if args.is_empty() {
+                Ok(Action::Tree {
+                    duplicates,
+                    invert,
+                    format,
+                    offline,
+                })
+            } else {
+                Err(Error::ExtraArgs("tree".to_string(), args))
+            }
+        }
+        "vendor" => {
+            if args.is_empty() {
+                Ok(Action::Vendor)
+            } else {
+                Err(Error::ExtraArgs("vendor".to_string(), args))
+            }
+        }
         "-v" | "--version" | "version" => Ok(Action::Version),
         "-h" | "--help" => Ok(Action::Help { action: None }),
         "help" => {
@@ -195,7 +450,23 @@ fn parse_args(mut args: Vec<String>) -> Result<Action, Error> {
                 let action = args.remove(0);
                 if matches!(
                     action.as_str(),
-                    "new" | "init" | "clean" | "build" | "run" | "test" | "clangd" | "toolchains"
+                    "new"
+                        | "init"
+                        | "clean"
+                        | "build"
+                        | "run"
+                        | "test"
+                        | "clangd"
+                        | "update"
+                        | "vendor"
+                        | "publish"
+                        | "add"
+                        | "remove"
+                        | "tree"
+                        | "install"
+                        | "package"
+                        | "uninstall"
+                        | "toolchains"
                 ) && args.is_empty()
                 {
                     Ok(Action::Help { action: Some(action) })
@@ -222,6 +493,29 @@ impl<T> RemoveIf for Vec<T> {
     }
 }
 
+/// removes '<name> <value>' or '<name>=<value>', 'Some(None)' if the option is given without a value
+fn remove_value(args: &mut Vec<String>, names: &[&str]) -> Option<Option<String>> {
+    if let Some(i) = args.iter().position(|a| names.contains(&a.as_str())) {
+        args.remove(i);
+        return Some((i < args.len()).then(|| args.remove(i)));
+    }
+    let i = args
+        .iter()
+        .position(|a| names.iter().any(|n| a.strip_prefix(n).is_some_and(|v| v.starts_with('='))))?;
+    let arg = args.remove(i);
+    Some(arg.split_once('=').map(|(_, v)| v.to_string()))
+}
+
+/// '--prefix <DIR>' and '--destdir <DIR>' of 'install' and 'uninstall'
+fn parse_prefix(action: &str, args: &mut Vec<String>) -> Result<(Option<PathBuf>, Option<PathBuf>), Error> {
+    let mut value = |name: &str| match remove_value(args, &[name]) {
+        Some(Some(value)) => Ok(Some(PathBuf::from(value))),
+        Some(None) => Err(Error::MissingOption(action.to_string(), format!("{name} <DIR>"))),
+        None => Ok(None),
+    };
+    Ok((value("--prefix")?, value("--destdir")?))
+}
+
 fn parse_toolchain(toolchain: Option<String>) -> Result<ToolChain, Error> {
     if let Some(tc) = toolchain {
         let tc = if let Some(tc) = tc.strip_prefix("-t=") {
@@ -459,6 +753,224 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn parse_action_build_5() {
+        let result = parse_args(vec!["build".to_string(), "--locked".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Build {
+                switches: BuildSwitches {
+                    locked: true,
+                    ..Default::default()
+                },
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_action_build_6() {
+        let result = parse_args(vec!["build".to_string(), "--offline".to_string(), "--locked".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Build {
+                switches: BuildSwitches {
+                    locked: true,
+                    offline: true,
+                    ..Default::default()
+                },
+            }
+        );
+    }
+
+    #[test]
+    pub fn parse_action_publish() {
+        let result = parse_args(vec!["publish".to_string(), "--registry".to_string(), "/srv/index".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Publish {
+                registry: PathBuf::from("/srv/index")
+            }
+        );
+        let result = parse_args(vec!["publish".to_string(), "--registry=../index".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Publish {
+                registry: PathBuf::from("../index")
+            }
+        );
+        assert!(parse_args(vec!["publish".to_string()]).is_err());
+        assert!(parse_args(vec!["publish".to_string(), "--registry".to_string()]).is_err());
+    }
+
+    #[test]
+    pub fn parse_action_add() {
+        let args = |a: &[&str]| parse_args(a.iter().map(|s| s.to_string()).collect());
+        assert_eq!(
+            args(&["add", "mylib", "--path", "../mylib"]).unwrap(),
+            Action::Add {
+                name: "mylib".to_string(),
+                dependency: Dependency::Package {
+                    src: PathBuf::from("../mylib"),
+                    targets: Vec::new(),
+                    recipe: None,
+                    features: Vec::new(),
+                    version: None,
+                },
+                dev: false,
+            }
+        );
+        assert_eq!(
+            args(&["add", "fmt", "--git", "https://github.com/fmtlib/fmt.git", "--tag=10.2.1"]).unwrap(),
+            Action::Add {
+                name: "fmt".to_string(),
+                dependency: Dependency::Git {
+                    git: "https://github.com/fmtlib/fmt.git".to_string(),
+                    tag: Some("10.2.1".to_string()),
+                    branch: None,
+                    rev: None,
+                    path: None,
+                    headers: None,
+                    recipe: None,
+                    features: Vec::new(),
+                },
+                dev: false,
+            }
+        );
+        assert_eq!(
+            args(&["add", "--dev", "m", "--system"]).unwrap(),
+            Action::Add {
+                name: "m".to_string(),
+                dependency: Dependency::System {
+                    system: PathBuf::from("m")
+                },
+                dev: true,
+            }
+        );
+        assert_eq!(
+            args(&["add", "fmt@10.2", "-F", "os,color"]).unwrap(),
+            Action::Add {
+                name: "fmt".to_string(),
+                dependency: Dependency::Registry {
+                    version: "10.2".to_string(),
+                    registry: None,
+                    features: vec!["os".to_string(), "color".to_string()],
+                },
+                dev: false,
+            }
+        );
+        assert!(args(&["add", "fmt"]).is_err());
+        assert!(args(&["add", "fmt", "--path", "a", "--system"]).is_err());
+        assert!(args(&["add", "fmt", "--path", "a", "--tag", "1.0"]).is_err());
+        assert!(args(&["add", "--path", "a"]).is_err());
+        assert_eq!(
+            args(&["rm", "fmt", "--dev"]).unwrap(),
+            Action::Remove {
+                name: "fmt".to_string(),
+                dev: true
+            }
+        );
+        assert!(args(&["remove"]).is_err());
+    }
+
+    #[test]
+    pub fn parse_action_tree() {
+        let result = parse_args(vec!["tree".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Tree {
+                duplicates: false,
+                invert: None,
+                format: TreeFormat::Text,
+                offline: false,
+            }
+        );
+        let result = parse_args(vec![
+            "tree".to_string(),
+            "-i".to_string(),
+            "fmt".to_string(),
+            "--format=dot".to_string(),
+        ]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Tree {
+                duplicates: false,
+                invert: Some("fmt".to_string()),
+                format: TreeFormat::Dot,
+                offline: false,
+            }
+        );
+        let result = parse_args(vec!["tree".to_string(), "--duplicates".to_string()]);
+        assert!(matches!(result.unwrap(), Action::Tree { duplicates: true, .. }));
+        let result = parse_args(vec!["tree".to_string(), "--offline".to_string()]);
+        assert!(matches!(result.unwrap(), Action::Tree { offline: true, .. }));
+        assert!(parse_args(vec!["tree".to_string(), "--invert".to_string()]).is_err());
+        assert!(parse_args(vec!["tree".to_string(), "--format".to_string(), "json".to_string()]).is_err());
+    }
+
+    #[test]
+    pub fn parse_action_update() {
+        let result = parse_args(vec!["update".to_string()]);
+        assert_eq!(result.unwrap(), Action::Update { package: None });
+        let result = parse_args(vec!["update".to_string(), "SFML".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Update {
+                package: Some("SFML".to_string())
+            }
+        );
+        let result = parse_args(vec!["update".to_string(), "SFML".to_string(), "fmt".to_string()]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    pub fn parse_action_install() {
+        let result = parse_args(vec!["install".to_string(), "--prefix".to_string(), "/opt/foo".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Install {
+                switches: BuildSwitches {
+                    profile: Profile::Release,
+                    ..Default::default()
+                },
+                prefix: Some(PathBuf::from("/opt/foo")),
+                destdir: None,
+            }
+        );
+        let result = parse_args(vec!["uninstall".to_string(), "--destdir=stage".to_string()]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Uninstall {
+                prefix: None,
+                destdir: Some(PathBuf::from("stage"))
+            }
+        );
+        assert!(parse_args(vec!["install".to_string(), "--prefix".to_string()]).is_err());
+        assert!(parse_args(vec!["uninstall".to_string(), "--release".to_string()]).is_err());
+    }
+
+    #[test]
+    pub fn parse_action_package() {
+        let result = parse_args(vec!["package".to_string()]);
+        assert!(matches!(result.unwrap(), Action::Package { profiles, .. } if profiles == [Profile::Debug, Profile::Release]));
+        let result = parse_args(vec![
+            "package".to_string(),
+            "--profiles=release,dist".to_string(),
+            "-t=gcc".to_string(),
+        ]);
+        assert_eq!(
+            result.unwrap(),
+            Action::Package {
+                switches: BuildSwitches {
+                    profile: Profile::Release,
+                    toolchain: ToolChain::Gcc,
+                    ..Default::default()
+                },
+                profiles: vec![Profile::Release, Profile::Custom("dist".to_string())],
+            }
+        );
+        assert!(parse_args(vec!["package".to_string(), "--profiles".to_string()]).is_err());
+    }
+
     #[test]
     pub fn parse_action_error_1() {
         let result = parse_args(vec!["abc".to_string(), "--release".to_string()]);
@@ -9,7 +9,7 @@ mod config;
 mod log;
 
 use error::Error;
-use std::process::ExitCode;
+use std::{path::PathBuf, process::ExitCode};
 
 macro_rules! exit_failure {
     ($($arg:tt)*) => { {
@@ -19,6 +19,11 @@ macro_rules! exit_failure {
 }
 
 fn read_manifest() -> Result<String, Error> {
+    Ok(std::fs::read_to_string(manifest_path()?)?)
+}
+
+/// the manifest of the project in the working directory, platform specific manifests take precedence
+fn manifest_path() -> Result<PathBuf, Error> {
     let prefix = if cfg!(windows) {
         "win."
     } else if cfg!(target_os = "linux") {
@@ -36,13 +41,13 @@ fn read_manifest() -> Result<String, Error> {
     let def2 = "vango.toml";
 
     if std::fs::exists(&os1).unwrap() {
-        Ok(std::fs::read_to_string(&os1)?)
+        Ok(os1.into())
     } else if std::fs::exists(&os2).unwrap() {
-        Ok(std::fs::read_to_string(&os2)?)
+        Ok(os2.into())
     } else if std::fs::exists(def1).unwrap() {
-        Ok(std::fs::read_to_string(def1)?)
+        Ok(def1.into())
     } else if std::fs::exists(def2).unwrap() {
-        Ok(std::fs::read_to_string(def2)?)
+        Ok(def2.into())
     } else {
         Err(Error::MissingBuildScript(
             std::env::current_dir().unwrap().file_name().unwrap().into(),
@@ -83,18 +88,34 @@ fn main() -> ExitCode {
 
         match cmd {
             input::Action::Build { switches } => {
-                action::build(&build, &switches, false).unwrap_or_else(|e| exit_failure!("{}", e));
+                let mut resolver = fetch::Resolver::new(&build, switches.locked, switches.offline).unwrap_or_else(|e| exit_failure!("{}", e));
+                action::build(&build, &switches, &mut resolver, false).unwrap_or_else(|e| exit_failure!("{}", e));
+                resolver.finish().unwrap_or_else(|e| exit_failure!("{}", e));
             }
             input::Action::Run { switches, args } => {
                 if build.kind.is_lib() {
                     exit_failure!("{}", Error::LibNotExe(build.name));
                 }
-                action::build(&build, &switches, false).unwrap_or_else(|e| exit_failure!("{}", e));
-                return action::run(&build.name, &switches, args).unwrap_or_else(|e| exit_failure!("{}", e));
+                let mut resolver = fetch::Resolver::new(&build, switches.locked, switches.offline).unwrap_or_else(|e| exit_failure!("{}", e));
+                let interface = action::build(&build, &switches, &mut resolver, false).unwrap_or_else(|e| exit_failure!("{}", e));
+                resolver.finish().unwrap_or_else(|e| exit_failure!("{}", e));
+                return action::run(&build.name, &switches, &interface.shared_libs, args).unwrap_or_else(|e| exit_failure!("{}", e));
             }
             input::Action::Test { switches, args } => {
-                action::build(&build, &switches, true).unwrap_or_else(|e| exit_failure!("{}", e));
-                return action::test(build, &switches, args).unwrap_or_else(|e| exit_failure!("{}", e));
+                let mut resolver = fetch::Resolver::new(&build, switches.locked, switches.offline).unwrap_or_else(|e| exit_failure!("{}", e));
+                action::build(&build, &switches, &mut resolver, true).unwrap_or_else(|e| exit_failure!("{}", e));
+                let code = action::test(build, &switches, &mut resolver, args).unwrap_or_else(|e| exit_failure!("{}", e));
+                resolver.finish().unwrap_or_else(|e| exit_failure!("{}", e));
+                return code;
+            }
+            input::Action::Install { switches, prefix, destdir } => {
+                action::install(&build, &switches, prefix.as_deref(), destdir.as_deref()).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Package { switches, profiles } => {
+                action::package(&build, &switches, &profiles).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Uninstall { prefix, destdir } => {
+                action::uninstall(&build, prefix.as_deref(), destdir.as_deref()).unwrap_or_else(|e| exit_failure!("{}", e));
             }
             input::Action::Clean => {
                 action::clean(&build).unwrap_or_else(|e| exit_failure!("{}", e));
@@ -102,6 +123,29 @@ fn main() -> ExitCode {
             input::Action::Clangd => {
                 action::clangd(&build, false).unwrap_or_else(|e| exit_failure!("{}", e));
             }
+            input::Action::Update { package } => {
+                action::update(&build, package.as_deref()).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Publish { registry } => {
+                action::publish(&build, &registry).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Add { name, dependency, dev } => {
+                action::add(&build, &name, &dependency, dev).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Remove { name, dev } => {
+                action::remove(&build, &name, dev).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Tree {
+                duplicates,
+                invert,
+                format,
+                offline,
+            } => {
+                action::tree(&build, duplicates, invert.as_deref(), format, offline).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
+            input::Action::Vendor => {
+                action::vendor(&build).unwrap_or_else(|e| exit_failure!("{}", e));
+            }
             _ => unreachable!(),
         }
     }